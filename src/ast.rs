@@ -1,48 +1,118 @@
-use crate::token::Token;
+use crate::token::{Span, Token};
 
+// Every node carries the span of source it was parsed from as its last field
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub enum Statement {
   // Identifier, Expression
-  Let(Token, Expression),
-  Return(Expression),
+  Let(Token, Expression, Span),
+  Return(Expression, Span),
   Expression(Expression),
+  // Value of the loop
+  Break(Option<Expression>, Span),
+  Continue(Span),
 }
 
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub enum Expression {
-  Ident(Token),
-  Integer(Token),
+  Ident(Token, Span),
+  Integer(Token, Span),
+  Float(Token, Span),
+  String(Token, Span),
   // Token, Right
-  Prefix(Token, Box<Expression>),
+  Prefix(Token, Box<Expression>, Span),
   // Token, Left, Right
-  Infix(Token, Box<Expression>, Box<Expression>),
-  Boolean(Token),
+  Infix(Token, Box<Expression>, Box<Expression>, Span),
+  Boolean(Token, Span),
   // Condition, Consequence, Alternative
-  If(Box<Expression>, BlockStatement, Option<BlockStatement>),
+  If(
+    Box<Expression>,
+    BlockStatement,
+    Option<BlockStatement>,
+    Span,
+  ),
   // params, body
-  Function(Vec<Expression>, BlockStatement),
+  Function(Vec<Expression>, BlockStatement, Span),
   // function / identifier, arguments
-  Call(Box<Expression>, Vec<Expression>),
+  Call(Box<Expression>, Vec<Expression>, Span),
+  Array(Vec<Expression>, Span),
+  // key, value pairs in source order
+  Hash(Vec<(Expression, Expression)>, Span),
+  // Left, Index
+  Index(Box<Expression>, Box<Expression>, Span),
+  // `=` or a compound operator such as `+=`, Identifier, Value
+  Assign(Token, Token, Box<Expression>, Span),
+  // Condition, Body
+  While(Box<Expression>, BlockStatement, Span),
+  // Loop variable, Collection, Body
+  For(Token, Box<Expression>, BlockStatement, Span),
+}
+
+impl Expression {
+  pub fn span(&self) -> Span {
+    match self {
+      Expression::Ident(_, span)
+      | Expression::Integer(_, span)
+      | Expression::Float(_, span)
+      | Expression::String(_, span)
+      | Expression::Prefix(_, _, span)
+      | Expression::Infix(_, _, _, span)
+      | Expression::Boolean(_, span)
+      | Expression::If(_, _, _, span)
+      | Expression::Function(_, _, span)
+      | Expression::Call(_, _, span)
+      | Expression::Array(_, span)
+      | Expression::Hash(_, span)
+      | Expression::Index(_, _, span)
+      | Expression::Assign(_, _, _, span)
+      | Expression::While(_, _, span)
+      | Expression::For(_, _, _, span) => *span,
+    }
+  }
 }
 
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub enum Precedence {
   LOWEST,
+  ASSIGN,
+  RANGE,
+  OR,
+  AND,
   EQUALS,
   LESSGREATER,
+  BITOR,
+  BITXOR,
+  BITAND,
+  SHIFT,
   SUM,
   PRODUCT,
   PREFIX,
+  POWER,
   CALL,
+  INDEX,
 }
 
 pub fn get_precedence(token: &Token) -> Precedence {
   match token {
+    Token::ASSIGN
+    | Token::PLUSASSIGN
+    | Token::MINUSASSIGN
+    | Token::ASTERISKASSIGN
+    | Token::SLASHASSIGN => Precedence::ASSIGN,
+    Token::DOTDOT | Token::DOTDOTEQ => Precedence::RANGE,
+    Token::OR => Precedence::OR,
+    Token::AND => Precedence::AND,
     Token::EQ | Token::NOTEQ => Precedence::EQUALS,
-    Token::LT | Token::GT => Precedence::LESSGREATER,
+    Token::LT | Token::GT | Token::LTEQ | Token::GTEQ => Precedence::LESSGREATER,
+    Token::PIPE => Precedence::BITOR,
+    Token::CARET => Precedence::BITXOR,
+    Token::AMPERSAND => Precedence::BITAND,
+    Token::SHL | Token::SHR => Precedence::SHIFT,
     Token::PLUS | Token::MINUS => Precedence::SUM,
-    Token::SLASH | Token::ASTERISK => Precedence::PRODUCT,
+    Token::SLASH | Token::ASTERISK | Token::PERCENT => Precedence::PRODUCT,
+    // Binds tighter than a prefix on its left, so `-2 ** 2` is `-(2 ** 2)`
+    Token::POWER => Precedence::POWER,
     Token::LPAREN => Precedence::CALL,
+    Token::LBRACKET => Precedence::INDEX,
     _ => Precedence::LOWEST,
   }
 }
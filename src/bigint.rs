@@ -0,0 +1,572 @@
+use std::{
+  cmp::Ordering,
+  fmt,
+  ops::{Add, BitAnd, BitOr, BitXor, Mul, Neg, Not, Shl, Shr, Sub},
+};
+
+// An arbitrary precision integer, for values that don't fit in an `isize`. The magnitude is stored
+// in base 2^32 with the least significant digit first and no leading zero digits, so every value
+// has exactly one representation and zero is never negative.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct BigInt {
+  negative: bool,
+  digits: Vec<u32>,
+}
+
+impl BigInt {
+  fn new(negative: bool, mut digits: Vec<u32>) -> Self {
+    trim(&mut digits);
+    return BigInt {
+      negative: negative && !digits.is_empty(),
+      digits,
+    };
+  }
+
+  // Parses an unsigned number written in `radix`, returns None on an empty string or invalid digit
+  pub fn from_str_radix(src: &str, radix: u32) -> Option<Self> {
+    if src.is_empty() {
+      return None;
+    }
+
+    let mut digits = vec![];
+    for ch in src.chars() {
+      mul_add_small(&mut digits, radix, ch.to_digit(radix)?);
+    }
+
+    return Some(BigInt::new(false, digits));
+  }
+
+  pub fn to_isize(&self) -> Option<isize> {
+    if self.digits.len() > 2 {
+      return None;
+    }
+
+    let magnitude = self
+      .digits
+      .iter()
+      .rev()
+      .fold(0u64, |acc, digit| (acc << 32) | *digit as u64) as i128;
+    let value = if self.negative { -magnitude } else { magnitude };
+    return isize::try_from(value).ok();
+  }
+
+  // Rounds to the nearest float, values beyond the float range become infinite
+  pub fn to_f64(&self) -> f64 {
+    let magnitude = self
+      .digits
+      .iter()
+      .rev()
+      .fold(0.0, |acc, digit| acc * 4294967296.0 + *digit as f64);
+    if self.negative {
+      return -magnitude;
+    }
+    return magnitude;
+  }
+
+  // The integer part of `num`, None for NaN and infinities
+  pub fn from_f64(num: f64) -> Option<Self> {
+    if !num.is_finite() {
+      return None;
+    }
+    if num.abs() < 1.0 {
+      return Some(BigInt::from(0));
+    }
+
+    // Anything of magnitude 1 or more is a normal float, `mantissa * 2^exponent` with an implicit
+    // leading one bit in the mantissa
+    let bits = num.to_bits();
+    let exponent = ((bits >> 52) & 0x7ff) as i32 - 1075;
+    let mantissa = BigInt::from(((bits & ((1 << 52) - 1)) | (1 << 52)) as isize);
+    let magnitude = if exponent < 0 {
+      &mantissa >> exponent.unsigned_abs()
+    } else {
+      &mantissa << exponent as u32
+    };
+
+    if num < 0.0 {
+      return Some(-magnitude);
+    }
+    return Some(magnitude);
+  }
+
+  // Number of bits in the magnitude, zero for zero
+  pub fn bits(&self) -> u64 {
+    match self.digits.last() {
+      Some(top) => return self.digits.len() as u64 * 32 - top.leading_zeros() as u64,
+      None => return 0,
+    }
+  }
+
+  pub fn is_zero(&self) -> bool {
+    return self.digits.is_empty();
+  }
+
+  pub fn is_negative(&self) -> bool {
+    return self.negative;
+  }
+
+  // Truncating division like Rust's integers, the remainder takes the sign of `self`. None when
+  // dividing by zero.
+  pub fn checked_div_rem(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+    if other.is_zero() {
+      return None;
+    }
+
+    let (quotient, remainder) = div_rem_magnitude(&self.digits, &other.digits);
+    return Some((
+      BigInt::new(self.negative != other.negative, quotient),
+      BigInt::new(self.negative, remainder),
+    ));
+  }
+
+  pub fn pow(&self, mut exp: u32) -> BigInt {
+    let mut base = self.clone();
+    let mut result = BigInt::from(1);
+    while exp > 0 {
+      if exp & 1 == 1 {
+        result = &result * &base;
+      }
+      exp >>= 1;
+      if exp > 0 {
+        base = &base * &base;
+      }
+    }
+
+    return result;
+  }
+
+  // Two's complement digits, sign extended to `len` digits
+  fn to_twos_complement(&self, len: usize) -> Vec<u32> {
+    let mut digits = self.digits.clone();
+    digits.resize(len, 0);
+    if self.negative {
+      negate_digits(&mut digits);
+    }
+    return digits;
+  }
+
+  fn from_twos_complement(mut digits: Vec<u32>) -> Self {
+    let negative = digits.last().is_some_and(|digit| digit >> 31 == 1);
+    if negative {
+      negate_digits(&mut digits);
+    }
+    return BigInt::new(negative, digits);
+  }
+
+  fn bitwise(&self, other: &BigInt, op: fn(u32, u32) -> u32) -> BigInt {
+    // One extra digit so the sign bit is never lost
+    let len = self.digits.len().max(other.digits.len()) + 1;
+    let digits = self
+      .to_twos_complement(len)
+      .into_iter()
+      .zip(other.to_twos_complement(len))
+      .map(|(a, b)| op(a, b))
+      .collect();
+    return BigInt::from_twos_complement(digits);
+  }
+}
+
+impl From<isize> for BigInt {
+  fn from(num: isize) -> Self {
+    let magnitude = num.unsigned_abs() as u64;
+    return BigInt::new(num < 0, vec![magnitude as u32, (magnitude >> 32) as u32]);
+  }
+}
+
+impl Ord for BigInt {
+  fn cmp(&self, other: &Self) -> Ordering {
+    match (self.negative, other.negative) {
+      (false, true) => return Ordering::Greater,
+      (true, false) => return Ordering::Less,
+      (false, false) => return cmp_magnitude(&self.digits, &other.digits),
+      (true, true) => return cmp_magnitude(&other.digits, &self.digits),
+    }
+  }
+}
+
+impl PartialOrd for BigInt {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    return Some(self.cmp(other));
+  }
+}
+
+impl Add for &BigInt {
+  type Output = BigInt;
+
+  fn add(self, other: &BigInt) -> BigInt {
+    if self.negative == other.negative {
+      return BigInt::new(self.negative, add_magnitude(&self.digits, &other.digits));
+    }
+
+    // Opposite signs, the larger magnitude decides the sign of the result
+    match cmp_magnitude(&self.digits, &other.digits) {
+      Ordering::Less => {
+        return BigInt::new(other.negative, sub_magnitude(&other.digits, &self.digits))
+      }
+      _ => return BigInt::new(self.negative, sub_magnitude(&self.digits, &other.digits)),
+    }
+  }
+}
+
+impl Sub for &BigInt {
+  type Output = BigInt;
+
+  fn sub(self, other: &BigInt) -> BigInt {
+    return self + &-other.clone();
+  }
+}
+
+impl Mul for &BigInt {
+  type Output = BigInt;
+
+  fn mul(self, other: &BigInt) -> BigInt {
+    return BigInt::new(
+      self.negative != other.negative,
+      mul_magnitude(&self.digits, &other.digits),
+    );
+  }
+}
+
+impl Neg for BigInt {
+  type Output = BigInt;
+
+  fn neg(self) -> BigInt {
+    return BigInt::new(!self.negative, self.digits);
+  }
+}
+
+// `!x` is `-x - 1` in two's complement
+impl Not for &BigInt {
+  type Output = BigInt;
+
+  fn not(self) -> BigInt {
+    return -(self + &BigInt::from(1));
+  }
+}
+
+impl BitAnd for &BigInt {
+  type Output = BigInt;
+
+  fn bitand(self, other: &BigInt) -> BigInt {
+    return self.bitwise(other, |a, b| a & b);
+  }
+}
+
+impl BitOr for &BigInt {
+  type Output = BigInt;
+
+  fn bitor(self, other: &BigInt) -> BigInt {
+    return self.bitwise(other, |a, b| a | b);
+  }
+}
+
+impl BitXor for &BigInt {
+  type Output = BigInt;
+
+  fn bitxor(self, other: &BigInt) -> BigInt {
+    return self.bitwise(other, |a, b| a ^ b);
+  }
+}
+
+impl Shl<u32> for &BigInt {
+  type Output = BigInt;
+
+  fn shl(self, amount: u32) -> BigInt {
+    let mut digits = vec![0; (amount / 32) as usize];
+    let bits = amount % 32;
+    let mut carry = 0;
+    for digit in &self.digits {
+      let shifted = (*digit as u64) << bits;
+      digits.push(shifted as u32 | carry);
+      carry = (shifted >> 32) as u32;
+    }
+    digits.push(carry);
+
+    return BigInt::new(self.negative, digits);
+  }
+}
+
+// Rounds towards negative infinity like an arithmetic shift on two's complement
+impl Shr<u32> for &BigInt {
+  type Output = BigInt;
+
+  fn shr(self, amount: u32) -> BigInt {
+    if self.negative {
+      return !&(&(!self) >> amount);
+    }
+
+    let skip = (amount / 32) as usize;
+    let bits = amount % 32;
+    if skip >= self.digits.len() {
+      return BigInt::from(0);
+    }
+
+    let digits = &self.digits[skip..];
+    let shifted = (0..digits.len())
+      .map(|i| {
+        let high = digits.get(i + 1).copied().unwrap_or(0) as u64;
+        (((high << 32) | digits[i] as u64) >> bits) as u32
+      })
+      .collect();
+    return BigInt::new(false, shifted);
+  }
+}
+
+impl fmt::Display for BigInt {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if self.is_zero() {
+      return write!(f, "0");
+    }
+
+    // Peel off 9 decimal digits at a time, least significant chunk first
+    const CHUNK: u32 = 1_000_000_000;
+    let mut chunks = vec![];
+    let mut digits = self.digits.clone();
+    while !digits.is_empty() {
+      let (quotient, remainder) = div_rem_small(&digits, CHUNK);
+      chunks.push(remainder);
+      digits = quotient;
+    }
+
+    if self.negative {
+      write!(f, "-")?;
+    }
+    write!(f, "{}", chunks.pop().unwrap())?;
+    for chunk in chunks.iter().rev() {
+      write!(f, "{:09}", chunk)?;
+    }
+    return Ok(());
+  }
+}
+
+// Digits in base 2^32 are no help when reading tokens or the AST
+impl fmt::Debug for BigInt {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self)
+  }
+}
+
+fn trim(digits: &mut Vec<u32>) {
+  while digits.last() == Some(&0) {
+    digits.pop();
+  }
+}
+
+fn cmp_magnitude(a: &[u32], b: &[u32]) -> Ordering {
+  if a.len() != b.len() {
+    return a.len().cmp(&b.len());
+  }
+  return a.iter().rev().cmp(b.iter().rev());
+}
+
+fn add_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+  let mut sum = Vec::with_capacity(a.len().max(b.len()) + 1);
+  let mut carry = 0u64;
+  for i in 0..a.len().max(b.len()) {
+    let total = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+    sum.push(total as u32);
+    carry = total >> 32;
+  }
+  sum.push(carry as u32);
+
+  trim(&mut sum);
+  return sum;
+}
+
+// `a` must not be smaller than `b`
+fn sub_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+  let mut difference = Vec::with_capacity(a.len());
+  let mut borrow = 0i64;
+  for (i, digit) in a.iter().enumerate() {
+    let mut total = *digit as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+    borrow = 0;
+    if total < 0 {
+      total += 1 << 32;
+      borrow = 1;
+    }
+    difference.push(total as u32);
+  }
+
+  trim(&mut difference);
+  return difference;
+}
+
+fn mul_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+  let mut product = vec![0u32; a.len() + b.len()];
+  for (i, x) in a.iter().enumerate() {
+    let mut carry = 0u64;
+    for (j, y) in b.iter().enumerate() {
+      let total = product[i + j] as u64 + *x as u64 * *y as u64 + carry;
+      product[i + j] = total as u32;
+      carry = total >> 32;
+    }
+    product[i + b.len()] = carry as u32;
+  }
+
+  trim(&mut product);
+  return product;
+}
+
+fn mul_add_small(digits: &mut Vec<u32>, mul: u32, add: u32) {
+  let mut carry = add as u64;
+  for digit in digits.iter_mut() {
+    let total = *digit as u64 * mul as u64 + carry;
+    *digit = total as u32;
+    carry = total >> 32;
+  }
+  if carry > 0 {
+    digits.push(carry as u32);
+  }
+}
+
+fn div_rem_small(digits: &[u32], divisor: u32) -> (Vec<u32>, u32) {
+  let mut quotient = vec![0u32; digits.len()];
+  let mut remainder = 0u64;
+  for i in (0..digits.len()).rev() {
+    let current = (remainder << 32) | digits[i] as u64;
+    quotient[i] = (current / divisor as u64) as u32;
+    remainder = current % divisor as u64;
+  }
+
+  trim(&mut quotient);
+  return (quotient, remainder as u32);
+}
+
+// Schoolbook binary long division, `b` must not be zero
+fn div_rem_magnitude(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+  if cmp_magnitude(a, b) == Ordering::Less {
+    return (vec![], a.to_vec());
+  }
+  if let [divisor] = b {
+    let (quotient, remainder) = div_rem_small(a, *divisor);
+    return (quotient, vec![remainder]);
+  }
+
+  let mut quotient = vec![0u32; a.len()];
+  let mut remainder: Vec<u32> = vec![];
+  for bit in (0..a.len() * 32).rev() {
+    // remainder = remainder * 2 + the next bit of `a`
+    let mut carry = (a[bit / 32] >> (bit % 32)) & 1;
+    for digit in remainder.iter_mut() {
+      let next_carry = *digit >> 31;
+      *digit = (*digit << 1) | carry;
+      carry = next_carry;
+    }
+    if carry > 0 {
+      remainder.push(carry);
+    }
+
+    if cmp_magnitude(&remainder, b) != Ordering::Less {
+      remainder = sub_magnitude(&remainder, b);
+      quotient[bit / 32] |= 1 << (bit % 32);
+    }
+  }
+
+  return (quotient, remainder);
+}
+
+// In place two's complement negation, `!digits + 1`
+fn negate_digits(digits: &mut [u32]) {
+  let mut carry = true;
+  for digit in digits.iter_mut() {
+    let (sum, overflow) = (!*digit).overflowing_add(carry as u32);
+    *digit = sum;
+    carry = overflow;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::BigInt;
+
+  fn big(src: &str) -> BigInt {
+    match src.strip_prefix('-') {
+      Some(magnitude) => return -BigInt::from_str_radix(magnitude, 10).unwrap(),
+      None => return BigInt::from_str_radix(src, 10).unwrap(),
+    }
+  }
+
+  #[test]
+  fn div_rem_truncates_towards_zero() {
+    let cases = [
+      ("7", "2", "3", "1"),
+      ("-7", "2", "-3", "-1"),
+      ("7", "-2", "-3", "1"),
+      ("-7", "-2", "3", "-1"),
+      (
+        "-340282366920938463463374607431768211457",
+        "18446744073709551616",
+        "-18446744073709551616",
+        "-1",
+      ),
+    ];
+    for (left, right, quotient, remainder) in cases {
+      let (q, r) = big(left).checked_div_rem(&big(right)).unwrap();
+      assert_eq!(
+        (q, r),
+        (big(quotient), big(remainder)),
+        "{} / {}",
+        left,
+        right
+      );
+    }
+    assert!(big("1").checked_div_rem(&big("0")).is_none());
+  }
+
+  #[test]
+  fn bitwise_ops_use_twos_complement() {
+    let (a, b) = (big("-18446744073709551621"), big("12"));
+    assert_eq!(&a & &b, big("8"));
+    assert_eq!(&a | &b, big("-18446744073709551617"));
+    assert_eq!(&a ^ &b, big("-18446744073709551625"));
+    assert_eq!(!&a, big("18446744073709551620"));
+    assert_eq!(
+      &big("-1") & &big("-18446744073709551616"),
+      big("-18446744073709551616")
+    );
+  }
+
+  #[test]
+  fn shr_rounds_towards_negative_infinity() {
+    assert_eq!(
+      &big("-18446744073709551617") >> 1,
+      big("-9223372036854775809")
+    );
+    assert_eq!(&big("-18446744073709551617") >> 64, big("-2"));
+    assert_eq!(&big("-1") >> 100, big("-1"));
+    assert_eq!(&big("18446744073709551617") >> 64, big("1"));
+    assert_eq!(&big("1") << 64, big("18446744073709551616"));
+  }
+
+  #[test]
+  fn to_isize_covers_the_whole_range() {
+    assert_eq!(BigInt::from(isize::MAX).to_isize(), Some(isize::MAX));
+    assert_eq!(BigInt::from(isize::MIN).to_isize(), Some(isize::MIN));
+    assert_eq!((&BigInt::from(isize::MAX) + &big("1")).to_isize(), None);
+    assert_eq!((&BigInt::from(isize::MIN) - &big("1")).to_isize(), None);
+  }
+
+  #[test]
+  fn from_f64_keeps_the_integer_part() {
+    assert_eq!(BigInt::from_f64(0.9), Some(big("0")));
+    assert_eq!(BigInt::from_f64(-2.5), Some(big("-2")));
+    assert_eq!(BigInt::from_f64(1e20), Some(big("100000000000000000000")));
+    assert_eq!(BigInt::from_f64(-1e20), Some(big("-100000000000000000000")));
+    assert_eq!(BigInt::from_f64(f64::NAN), None);
+    assert_eq!(BigInt::from_f64(f64::INFINITY), None);
+  }
+
+  #[test]
+  fn display_pads_inner_chunks() {
+    assert_eq!(big("0").to_string(), "0");
+    assert_eq!(
+      big("-1000000000000000000001").to_string(),
+      "-1000000000000000000001"
+    );
+    assert_eq!(BigInt::from(isize::MIN).to_string(), isize::MIN.to_string());
+    assert_eq!(
+      big("2").pow(100).to_string(),
+      "1267650600228229401496703205376"
+    );
+  }
+}
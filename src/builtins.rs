@@ -0,0 +1,217 @@
+use crate::{
+  bigint::BigInt,
+  object::{EvalResult, Interrupt, Object, RuntimeError},
+};
+
+// Builtins are resolved by name after the environment, they are never bound with `let` so they
+// don't have to be valid minion names
+pub fn get(name: &str) -> Option<Object> {
+  let builtin: fn(Vec<Object>) -> EvalResult = match name {
+    "len" => len,
+    "puts" => puts,
+    "first" => first,
+    "last" => last,
+    "rest" => rest,
+    "push" => push,
+    "type" => type_of,
+    "exit" => exit,
+    "floor" => floor,
+    "ceil" => ceil,
+    "round" => round,
+    "sqrt" => sqrt,
+    "int" => int,
+    "float" => float,
+    _ => return None,
+  };
+
+  return Some(Object::Builtin(builtin));
+}
+
+fn check_args(name: &str, args: &[Object], expected: usize) -> Result<(), Interrupt> {
+  if args.len() != expected {
+    return Err(Interrupt::Error(RuntimeError::new(format!(
+      "Wrong number of arguments to `{}`: expected {}, got {}",
+      name,
+      expected,
+      args.len()
+    ))));
+  }
+
+  return Ok(());
+}
+
+fn unsupported(name: &str, arg: &Object) -> EvalResult {
+  return Interrupt::error(format!(
+    "Argument to `{}` not supported, got: {}",
+    name,
+    arg.type_name()
+  ));
+}
+
+fn len(args: Vec<Object>) -> EvalResult {
+  check_args("len", &args, 1)?;
+  match &args[0] {
+    Object::String(val) => Ok(Object::Integer(val.chars().count() as isize)),
+    Object::Array(elements) => Ok(Object::Integer(elements.len() as isize)),
+    Object::Hash(pairs) => Ok(Object::Integer(pairs.len() as isize)),
+    arg => unsupported("len", arg),
+  }
+}
+
+fn puts(args: Vec<Object>) -> EvalResult {
+  for arg in args {
+    println!("{}", arg);
+  }
+
+  return Ok(Object::NoOp);
+}
+
+fn first(args: Vec<Object>) -> EvalResult {
+  check_args("first", &args, 1)?;
+  match &args[0] {
+    Object::Array(elements) => match elements.first() {
+      Some(element) => Ok(element.clone()),
+      None => Interrupt::error("`first` called on an empty array".to_string()),
+    },
+    arg => unsupported("first", arg),
+  }
+}
+
+fn last(args: Vec<Object>) -> EvalResult {
+  check_args("last", &args, 1)?;
+  match &args[0] {
+    Object::Array(elements) => match elements.last() {
+      Some(element) => Ok(element.clone()),
+      None => Interrupt::error("`last` called on an empty array".to_string()),
+    },
+    arg => unsupported("last", arg),
+  }
+}
+
+fn rest(args: Vec<Object>) -> EvalResult {
+  check_args("rest", &args, 1)?;
+  match &args[0] {
+    Object::Array(elements) => Ok(Object::Array(elements.iter().skip(1).cloned().collect())),
+    arg => unsupported("rest", arg),
+  }
+}
+
+fn push(args: Vec<Object>) -> EvalResult {
+  check_args("push", &args, 2)?;
+  match &args[0] {
+    Object::Array(elements) => {
+      let mut elements = elements.clone();
+      elements.push(args[1].clone());
+      Ok(Object::Array(elements))
+    }
+    arg => unsupported("push", arg),
+  }
+}
+
+fn floor(args: Vec<Object>) -> EvalResult {
+  return round_with("floor", args, f64::floor);
+}
+
+fn ceil(args: Vec<Object>) -> EvalResult {
+  return round_with("ceil", args, f64::ceil);
+}
+
+// Rounds half way cases away from zero
+fn round(args: Vec<Object>) -> EvalResult {
+  return round_with("round", args, f64::round);
+}
+
+fn round_with(name: &str, args: Vec<Object>, round: fn(f64) -> f64) -> EvalResult {
+  check_args(name, &args, 1)?;
+  match &args[0] {
+    Object::Integer(_) | Object::BigInt(_) => Ok(args[0].clone()),
+    Object::Float(num) => float_to_int(name, round(*num)),
+    arg => unsupported(name, arg),
+  }
+}
+
+fn sqrt(args: Vec<Object>) -> EvalResult {
+  check_args("sqrt", &args, 1)?;
+  match args[0].to_f64() {
+    Some(num) => Ok(Object::Float(num.sqrt())),
+    None => unsupported("sqrt", &args[0]),
+  }
+}
+
+// Floats are truncated towards zero, strings are parsed as decimal integers
+fn int(args: Vec<Object>) -> EvalResult {
+  check_args("int", &args, 1)?;
+  match &args[0] {
+    Object::Integer(_) | Object::BigInt(_) => Ok(args[0].clone()),
+    Object::Float(num) => float_to_int("int", *num),
+    Object::String(val) => {
+      let trimmed = val.trim();
+      let (negative, digits) = match trimmed.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+      };
+      match BigInt::from_str_radix(digits, 10) {
+        Some(num) if negative => Ok(Object::from(-num)),
+        Some(num) => Ok(Object::from(num)),
+        None => Interrupt::error(format!("`int` cannot parse {:?} as an integer", val)),
+      }
+    }
+    arg => unsupported("int", arg),
+  }
+}
+
+fn float(args: Vec<Object>) -> EvalResult {
+  check_args("float", &args, 1)?;
+  match &args[0] {
+    Object::String(val) => match val.trim().parse::<f64>() {
+      Ok(num) => Ok(Object::Float(num)),
+      Err(_) => Interrupt::error(format!("`float` cannot parse {:?} as a float", val)),
+    },
+    arg => match arg.to_f64() {
+      Some(num) => Ok(Object::Float(num)),
+      None => unsupported("float", arg),
+    },
+  }
+}
+
+fn float_to_int(name: &str, num: f64) -> EvalResult {
+  match BigInt::from_f64(num) {
+    Some(int) => Ok(Object::from(int)),
+    None => Interrupt::error(format!("`{}` cannot convert {:?} to an integer", name, num)),
+  }
+}
+
+fn type_of(args: Vec<Object>) -> EvalResult {
+  check_args("type", &args, 1)?;
+  return Ok(Object::String(args[0].type_name().to_string()));
+}
+
+fn exit(args: Vec<Object>) -> EvalResult {
+  let code = match args.as_slice() {
+    [] => 0,
+    [Object::Integer(code)] => match u8::try_from(*code) {
+      Ok(code) => code,
+      Err(_) => {
+        return Interrupt::error(format!(
+          "Exit code must be between 0 and 255, got: {}",
+          code
+        ))
+      }
+    },
+    [Object::BigInt(code)] => {
+      return Interrupt::error(format!(
+        "Exit code must be between 0 and 255, got: {}",
+        code
+      ))
+    }
+    [arg] => return unsupported("exit", arg),
+    _ => {
+      return Interrupt::error(format!(
+        "Wrong number of arguments to `exit`: expected 0 or 1, got {}",
+        args.len()
+      ))
+    }
+  };
+
+  return Err(Interrupt::Exit(code));
+}
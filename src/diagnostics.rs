@@ -0,0 +1,189 @@
+use std::{env, fmt::Write, io::IsTerminal};
+
+use crate::{object::RuntimeError, parser::ParserError, token::Span};
+
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+// An error ready to be shown to the user, rendered rustc style with the offending source line
+#[derive(Debug)]
+pub struct Diagnostic {
+  pub message: String,
+  pub span: Option<Span>,
+  pub notes: Vec<String>,
+  pub help: Vec<String>,
+}
+
+impl Diagnostic {
+  pub fn error(message: String, span: Option<Span>) -> Self {
+    Diagnostic {
+      message,
+      span,
+      notes: vec![],
+      help: vec![],
+    }
+  }
+
+  pub fn with_note(mut self, note: String) -> Self {
+    self.notes.push(note);
+    return self;
+  }
+
+  pub fn with_help(mut self, help: String) -> Self {
+    self.help.push(help);
+    return self;
+  }
+
+  pub fn render(&self, file: &str, source: &str, color: bool) -> String {
+    let paint = |style: &str, text: &str| {
+      if color {
+        format!("{}{}{}", style, text, RESET)
+      } else {
+        text.to_string()
+      }
+    };
+
+    let mut out = String::new();
+    writeln!(
+      out,
+      "{}{}",
+      paint(RED, "error"),
+      paint(BOLD, &format!(": {}", self.message))
+    )
+    .unwrap();
+
+    let Some(span) = self.span else {
+      writeln!(out, " {} {}", paint(BLUE, "-->"), file).unwrap();
+      for note in &self.notes {
+        writeln!(out, " {} note: {}", paint(BLUE, "="), note).unwrap();
+      }
+      for help in &self.help {
+        writeln!(out, " {} help: {}", paint(BLUE, "="), help).unwrap();
+      }
+      return out;
+    };
+
+    // Spans should already fall on character boundaries, but a bad one mustn't crash the report
+    let start = source.floor_char_boundary(span.start);
+    let end = source.ceil_char_boundary(span.end);
+    let line_start = source[..start].rfind('\n').map_or(0, |idx| idx + 1);
+    let line_end = source[line_start..]
+      .find('\n')
+      .map_or(source.len(), |idx| line_start + idx);
+    let line = source[line_start..line_end].trim_end_matches('\r');
+
+    // Keep tabs in the padding so the carets line up with the source however tabs are displayed
+    let padding: String = source[line_start..start]
+      .chars()
+      .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+      .collect();
+    let width = source[start..end.clamp(start, line_end)]
+      .chars()
+      .count()
+      .max(1);
+
+    let gutter = " ".repeat(span.line.to_string().len());
+    let bar = paint(BLUE, "|");
+    writeln!(out, "{}{} {}:{}", gutter, paint(BLUE, "-->"), file, span).unwrap();
+    writeln!(out, "{} {}", gutter, bar).unwrap();
+    writeln!(
+      out,
+      "{} {} {}",
+      paint(BLUE, &span.line.to_string()),
+      bar,
+      line
+    )
+    .unwrap();
+    writeln!(
+      out,
+      "{} {} {}{}",
+      gutter,
+      bar,
+      padding,
+      paint(RED, &"^".repeat(width))
+    )
+    .unwrap();
+    for note in &self.notes {
+      writeln!(out, "{} {} note: {}", gutter, paint(BLUE, "="), note).unwrap();
+    }
+    for help in &self.help {
+      writeln!(out, "{} {} help: {}", gutter, paint(BLUE, "="), help).unwrap();
+    }
+
+    return out;
+  }
+}
+
+impl From<&ParserError> for Diagnostic {
+  fn from(error: &ParserError) -> Self {
+    let diagnostic = Diagnostic::error(error.message(), Some(error.span()));
+    match error {
+      ParserError::InvalidIdent(_, suggestions, exact, _) => {
+        let note = if *exact {
+          "identifiers must be exactly a minion name"
+        } else {
+          "identifiers must contain a minion name"
+        };
+        let diagnostic = diagnostic.with_note(note.to_string());
+        match suggestions.as_slice() {
+          [] => diagnostic,
+          [only] => diagnostic.with_help(format!("did you mean `{}`?", only)),
+          [rest @ .., last] => {
+            let rest: Vec<String> = rest.iter().map(|name| format!("`{}`", name)).collect();
+            diagnostic.with_help(format!("did you mean {} or `{}`?", rest.join(", "), last))
+          }
+        }
+      }
+      _ => diagnostic,
+    }
+  }
+}
+
+impl From<&RuntimeError> for Diagnostic {
+  fn from(error: &RuntimeError) -> Self {
+    Diagnostic::error(error.msg.clone(), error.span)
+  }
+}
+
+// Colors are only used when `stream` is a terminal, `NO_COLOR` turns them off everywhere
+pub fn color_default(stream: impl IsTerminal) -> bool {
+  return env::var_os("NO_COLOR").is_none() && stream.is_terminal();
+}
+
+#[cfg(test)]
+mod tests {
+  use std::rc::Rc;
+
+  use super::Diagnostic;
+  use crate::{lexer::Lexer, minions::MinionRoster, parser::Parser, token::Span};
+
+  #[test]
+  fn renders_non_ascii_illegal_characters() {
+    let source = "let kevin = é + 😀;";
+    let mut parser = Parser::new(Lexer::new(source), Rc::new(MinionRoster::default()));
+    parser.parse_program();
+
+    let rendered = Diagnostic::from(&parser.errors[0]).render("<test>", source, false);
+    assert!(
+      rendered.contains("1 | let kevin = é + 😀;\n"),
+      "{}",
+      rendered
+    );
+    assert!(rendered.contains("  |             ^\n"), "{}", rendered);
+  }
+
+  #[test]
+  fn snaps_spans_to_character_boundaries() {
+    let source = "é😀";
+    let span = Span {
+      start: 1,
+      end: 4,
+      line: 1,
+      col: 1,
+    };
+    let rendered = Diagnostic::error("bad".to_string(), Some(span)).render("<test>", source, false);
+    assert!(rendered.contains("  | ^^\n"), "{}", rendered);
+  }
+}
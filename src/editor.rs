@@ -0,0 +1,429 @@
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+const HISTORY_FILE: &str = ".minion_history";
+const MAX_HISTORY: usize = 1000;
+
+pub enum ReadLine {
+  Line(String),
+  Eof,
+  Cancelled,
+}
+
+enum Key {
+  Char(char),
+  Ctrl(char),
+  Enter,
+  Tab,
+  Backspace,
+  Delete,
+  Left,
+  Right,
+  Up,
+  Down,
+  Home,
+  End,
+  Escape,
+  Unknown,
+}
+
+// Line editing for the REPL: cursor movement, history (persisted in the home directory), reverse
+// search with Ctrl-R and tab completion. Only used when both stdin and stdout are terminals that
+// `stty` can put in raw mode.
+pub struct Editor {
+  history: Vec<String>,
+  history_path: Option<PathBuf>,
+  // Lines in the history file, which is appended to and only rewritten once it gets too long
+  saved_lines: usize,
+}
+
+impl Editor {
+  pub fn new() -> Self {
+    let history_path = env::var_os("HOME").map(|home| PathBuf::from(home).join(HISTORY_FILE));
+    let mut history: Vec<String> = match &history_path {
+      Some(path) => fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .map(String::from)
+        .collect(),
+      None => vec![],
+    };
+    let saved_lines = history.len();
+    let excess = history.len().saturating_sub(MAX_HISTORY);
+    history.drain(..excess);
+
+    Editor {
+      history,
+      history_path,
+      saved_lines,
+    }
+  }
+
+  pub fn is_supported() -> bool {
+    return cfg!(unix)
+      && io::stdin().is_terminal()
+      && io::stdout().is_terminal()
+      && RawMode::enable().is_ok();
+  }
+
+  pub fn add_history(&mut self, entry: &str) {
+    if entry.is_empty() || self.history.last().is_some_and(|last| last == entry) {
+      return;
+    }
+
+    self.history.push(entry.to_string());
+    if self.history.len() > MAX_HISTORY {
+      self.history.remove(0);
+    }
+
+    // History is a convenience, failing to save it shouldn't get in the way
+    let Some(path) = &self.history_path else {
+      return;
+    };
+    if self.saved_lines >= 2 * MAX_HISTORY {
+      let mut contents = self.history.join("\n");
+      contents.push('\n');
+      if fs::write(path, contents).is_ok() {
+        self.saved_lines = self.history.len();
+      }
+    } else if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+      if writeln!(file, "{}", entry).is_ok() {
+        self.saved_lines += 1;
+      }
+    }
+  }
+
+  // Reads a line with the terminal in raw mode, `words` are the candidates for tab completion
+  pub fn read_line(&mut self, prompt: &str, words: &[String]) -> io::Result<ReadLine> {
+    let _raw = RawMode::enable()?;
+    let mut line = LineState {
+      prompt,
+      buffer: vec![],
+      cursor: 0,
+    };
+    let mut history_idx = self.history.len();
+    let mut draft: Vec<char> = vec![];
+
+    line.redraw()?;
+    loop {
+      let key = read_key()?;
+      match key {
+        Key::Enter => {
+          print!("\r\n");
+          io::stdout().flush()?;
+          let mut text: String = line.buffer.iter().collect();
+          text.push('\n');
+          return Ok(ReadLine::Line(text));
+        }
+        Key::Ctrl('c') => {
+          print!("^C");
+          io::stdout().flush()?;
+          return Ok(ReadLine::Cancelled);
+        }
+        Key::Ctrl('d') if line.buffer.is_empty() => return Ok(ReadLine::Eof),
+        Key::Ctrl('r') => {
+          if let Some(found) = self.reverse_search(&mut line)? {
+            let mut text: String = found.iter().collect();
+            text.push('\n');
+            return Ok(ReadLine::Line(text));
+          }
+        }
+        Key::Up | Key::Ctrl('p') if history_idx > 0 => {
+          if history_idx == self.history.len() {
+            draft = line.buffer.clone();
+          }
+          history_idx -= 1;
+          line.set(self.history[history_idx].chars().collect());
+        }
+        Key::Down | Key::Ctrl('n') if history_idx < self.history.len() => {
+          history_idx += 1;
+          match self.history.get(history_idx) {
+            Some(entry) => line.set(entry.chars().collect()),
+            None => line.set(draft.clone()),
+          }
+        }
+        Key::Tab => line.complete(words)?,
+        key => line.edit(key),
+      }
+      line.redraw()?;
+    }
+  }
+
+  // Incremental search back through history. Returns the match when Enter accepts it, otherwise
+  // leaves the match (or the original line when cancelled) in `line` for further editing.
+  fn reverse_search(&self, line: &mut LineState) -> io::Result<Option<Vec<char>>> {
+    let original = line.buffer.clone();
+    let mut query = String::new();
+    let mut found_idx = self.history.len();
+    let mut found: Vec<char> = vec![];
+
+    loop {
+      print!(
+        "\r(reverse-i-search)'{}': {}\x1b[K",
+        query,
+        found.iter().collect::<String>()
+      );
+      io::stdout().flush()?;
+
+      let mut search_from = found_idx;
+      match read_key()? {
+        Key::Char(ch) => {
+          query.push(ch);
+          search_from = (found_idx + 1).min(self.history.len());
+        }
+        Key::Backspace => {
+          query.pop();
+          search_from = self.history.len();
+        }
+        Key::Ctrl('r') => (),
+        Key::Enter => {
+          print!("\r\n");
+          return Ok(Some(found));
+        }
+        Key::Ctrl('g') | Key::Ctrl('c') | Key::Escape => {
+          line.set(original);
+          return Ok(None);
+        }
+        _ => {
+          line.set(found);
+          return Ok(None);
+        }
+      }
+
+      if let Some(idx) = self.history[..search_from]
+        .iter()
+        .rposition(|entry| entry.contains(&query))
+      {
+        found_idx = idx;
+        found = self.history[idx].chars().collect();
+      }
+    }
+  }
+}
+
+struct LineState<'a> {
+  prompt: &'a str,
+  buffer: Vec<char>,
+  cursor: usize,
+}
+
+impl LineState<'_> {
+  fn set(&mut self, buffer: Vec<char>) {
+    self.cursor = buffer.len();
+    self.buffer = buffer;
+  }
+
+  fn redraw(&self) -> io::Result<()> {
+    let text: String = self.buffer.iter().collect();
+    print!("\r{}{}\x1b[K", self.prompt, text);
+    let behind = self.buffer.len() - self.cursor;
+    if behind > 0 {
+      print!("\x1b[{}D", behind);
+    }
+    return io::stdout().flush();
+  }
+
+  fn edit(&mut self, key: Key) {
+    match key {
+      Key::Char(ch) => {
+        self.buffer.insert(self.cursor, ch);
+        self.cursor += 1;
+      }
+      Key::Backspace | Key::Ctrl('h') if self.cursor > 0 => {
+        self.cursor -= 1;
+        self.buffer.remove(self.cursor);
+      }
+      Key::Delete | Key::Ctrl('d') if self.cursor < self.buffer.len() => {
+        self.buffer.remove(self.cursor);
+      }
+      Key::Left | Key::Ctrl('b') if self.cursor > 0 => self.cursor -= 1,
+      Key::Right | Key::Ctrl('f') if self.cursor < self.buffer.len() => self.cursor += 1,
+      Key::Home | Key::Ctrl('a') => self.cursor = 0,
+      Key::End | Key::Ctrl('e') => self.cursor = self.buffer.len(),
+      Key::Ctrl('u') => {
+        self.buffer.drain(..self.cursor);
+        self.cursor = 0;
+      }
+      Key::Ctrl('k') => {
+        self.buffer.truncate(self.cursor);
+      }
+      Key::Ctrl('w') => {
+        let end = self.cursor;
+        while self.cursor > 0 && self.buffer[self.cursor - 1] == ' ' {
+          self.cursor -= 1;
+        }
+        while self.cursor > 0 && self.buffer[self.cursor - 1] != ' ' {
+          self.cursor -= 1;
+        }
+        self.buffer.drain(self.cursor..end);
+      }
+      _ => (),
+    }
+  }
+
+  // Completes the identifier before the cursor as far as all candidates agree, listing them when
+  // there is nothing more to fill in
+  fn complete(&mut self, words: &[String]) -> io::Result<()> {
+    let start = self.buffer[..self.cursor]
+      .iter()
+      .rposition(|ch| !(ch.is_alphanumeric() || *ch == '_'))
+      .map_or(0, |idx| idx + 1);
+    let prefix: String = self.buffer[start..self.cursor].iter().collect();
+
+    let mut matches: Vec<&String> = words
+      .iter()
+      .filter(|word| word.starts_with(&prefix))
+      .collect();
+    matches.sort();
+    matches.dedup();
+
+    let Some(first) = matches.first() else {
+      return Ok(());
+    };
+    let common: String = first
+      .chars()
+      .enumerate()
+      .take_while(|(i, ch)| matches.iter().all(|word| word.chars().nth(*i) == Some(*ch)))
+      .map(|(_, ch)| ch)
+      .collect();
+
+    if common.chars().count() > prefix.chars().count() {
+      for ch in common.chars().skip(prefix.chars().count()) {
+        self.buffer.insert(self.cursor, ch);
+        self.cursor += 1;
+      }
+    } else if matches.len() > 1 {
+      let list: Vec<&str> = matches.iter().map(|word| word.as_str()).collect();
+      print!("\r\n{}\r\n", list.join("  "));
+    }
+
+    return Ok(());
+  }
+}
+
+fn read_byte() -> io::Result<u8> {
+  let mut byte = [0; 1];
+  loop {
+    match io::stdin().lock().read(&mut byte) {
+      Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+      Ok(_) => return Ok(byte[0]),
+      Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+      Err(err) => return Err(err),
+    }
+  }
+}
+
+fn read_key() -> io::Result<Key> {
+  let byte = read_byte()?;
+  let key = match byte {
+    b'\r' | b'\n' => Key::Enter,
+    b'\t' => Key::Tab,
+    0x7f => Key::Backspace,
+    0x1b => return read_escape(),
+    1..=26 => Key::Ctrl((b'a' + byte - 1) as char),
+    0x20..=0x7e => Key::Char(byte as char),
+    0xc0..=0xf7 => {
+      // Multi-byte UTF-8, the leading byte says how many continuation bytes follow
+      let len = if byte >= 0xf0 {
+        4
+      } else if byte >= 0xe0 {
+        3
+      } else {
+        2
+      };
+      let mut bytes = vec![byte];
+      for _ in 1..len {
+        bytes.push(read_byte()?);
+      }
+      match std::str::from_utf8(&bytes)
+        .ok()
+        .and_then(|s| s.chars().next())
+      {
+        Some(ch) => Key::Char(ch),
+        None => Key::Unknown,
+      }
+    }
+    _ => Key::Unknown,
+  };
+
+  return Ok(key);
+}
+
+// Arrow and navigation keys arrive as `ESC [ ...` or `ESC O ...` sequences
+fn read_escape() -> io::Result<Key> {
+  let kind = read_byte()?;
+  if kind != b'[' && kind != b'O' {
+    return Ok(Key::Escape);
+  }
+
+  let mut params = vec![];
+  let last = loop {
+    let byte = read_byte()?;
+    if (0x40..=0x7e).contains(&byte) {
+      break byte;
+    }
+    params.push(byte);
+  };
+
+  let key = match (params.as_slice(), last) {
+    (_, b'A') => Key::Up,
+    (_, b'B') => Key::Down,
+    (_, b'C') => Key::Right,
+    (_, b'D') => Key::Left,
+    (_, b'H') | (b"1", b'~') | (b"7", b'~') => Key::Home,
+    (_, b'F') | (b"4", b'~') | (b"8", b'~') => Key::End,
+    (b"3", b'~') => Key::Delete,
+    _ => Key::Unknown,
+  };
+
+  return Ok(key);
+}
+
+// Puts the terminal in raw mode with `stty` for as long as this is alive, restoring the previous
+// settings on drop. Output processing is left alone so printing works as usual.
+struct RawMode {
+  saved: String,
+}
+
+impl RawMode {
+  fn enable() -> io::Result<Self> {
+    let output = Command::new("stty")
+      .arg("-g")
+      .stdin(Stdio::inherit())
+      .output()?;
+    if !output.status.success() {
+      return Err(io::Error::other(
+        "stty could not read the terminal settings",
+      ));
+    }
+
+    let saved = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    stty(&[
+      "-icanon", "-echo", "-isig", "-iexten", "-ixon", "min", "1", "time", "0",
+    ])?;
+    return Ok(RawMode { saved });
+  }
+}
+
+impl Drop for RawMode {
+  fn drop(&mut self) {
+    let _ = stty(&[self.saved.as_str()]);
+  }
+}
+
+fn stty(args: &[&str]) -> io::Result<()> {
+  let status = Command::new("stty")
+    .args(args)
+    .stdin(Stdio::inherit())
+    .status()?;
+  if !status.success() {
+    return Err(io::Error::other(
+      "stty could not change the terminal settings",
+    ));
+  }
+
+  return Ok(());
+}
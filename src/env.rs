@@ -1,8 +1,8 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{cell::RefCell, collections::HashMap, fmt, rc::Rc};
 
 use crate::object::Object;
 
-#[derive(Default, Clone, Debug)]
+#[derive(Default, Clone)]
 pub struct Environment {
   pub store: HashMap<String, Object>,
   pub outer: Option<Rc<RefCell<Environment>>>,
@@ -20,6 +20,20 @@ impl Environment {
     self.store.insert(key, val);
   }
 
+  // Updates an existing binding in the innermost scope that declares it, returns false if no scope
+  // does
+  pub fn assign(&mut self, key: &String, val: Object) -> bool {
+    if let Some(slot) = self.store.get_mut(key) {
+      *slot = val;
+      return true;
+    }
+
+    match &self.outer {
+      Some(outer) => return outer.borrow_mut().assign(key, val),
+      None => return false,
+    }
+  }
+
   pub fn get(&self, key: &String) -> Option<Object> {
     match self.store.get(key) {
       Some(obj) => return Some(obj.clone()),
@@ -31,3 +45,14 @@ impl Environment {
     }
   }
 }
+
+// Closures capture the environment they are defined in, so a function bound in its own scope makes
+// the environment reference itself. Only print the names to avoid recursing forever.
+impl fmt::Debug for Environment {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("Environment")
+      .field("store", &self.store.keys().collect::<Vec<_>>())
+      .field("outer", &self.outer.is_some())
+      .finish()
+  }
+}
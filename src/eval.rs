@@ -1,115 +1,331 @@
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
 use std::io::{self, Write};
 use std::rc::Rc;
 
 use crate::ast::{BlockStatement, Expression, Program, Statement};
+use crate::bigint::BigInt;
+use crate::builtins;
 use crate::env::Environment;
-use crate::object::{EvalResult, Interrupt, Object};
+use crate::object::{cmp_numbers, EvalResult, Interrupt, Object, RuntimeError};
+use crate::sigint;
 use crate::token::Token;
 
+// Integers beyond this take too long to compute and print, `3 ** 4000000000` would run for hours
+const MAX_INTEGER_BITS: u64 = 1 << 17;
+
 pub struct Evaluator {
   pub env: Rc<RefCell<Environment>>,
+  // Number of function calls this evaluator is nested in
+  depth: usize,
+  // Calls that fit on the stack the evaluator runs on
+  max_depth: usize,
 }
 
 impl Evaluator {
-  pub fn new() -> Self {
+  pub fn new(max_depth: usize) -> Self {
     Evaluator {
       env: Rc::new(RefCell::new(Environment {
         store: HashMap::new(),
         outer: None,
       })),
+      depth: 0,
+      max_depth,
     }
   }
 
   pub fn eval(&mut self, program: Program) -> EvalResult {
-    let mut res: EvalResult = Ok(Object::NoOp);
+    let mut res = Object::NoOp;
     for statement in program {
-      res = self.eval_statement(statement);
-
-      if let Err(_) = res {
-        return res;
-      }
+      res = self.eval_statement(statement)?;
     }
 
-    return res;
+    return Ok(res);
   }
 
   fn eval_block(&mut self, block: BlockStatement) -> EvalResult {
-    let mut res: EvalResult = Ok(Object::NoOp);
+    let mut res = Object::NoOp;
     for statement in block {
-      res = self.eval_statement(statement);
-      if let Err(_) = res {
-        return res;
-      }
+      res = self.eval_statement(statement)?;
     }
-    return res;
+    return Ok(res);
   }
 
   fn eval_statement(&mut self, statement: Statement) -> EvalResult {
     match statement {
-      Statement::Let(Token::IDENT(var), expr) => {
+      // `let` always binds in the current scope, declaring a name that already exists there is
+      // allowed and replaces it. Use assignment to update a binding from an outer scope.
+      Statement::Let(Token::IDENT(var), expr, _) => {
         let res = self.eval_expression(expr)?;
         self.env.borrow_mut().insert(var, res);
         Ok(Object::NoOp)
       }
-      Statement::Return(expr) => Err(Interrupt::Return(self.eval_expression(expr)?)),
+      Statement::Return(expr, _) => Err(Interrupt::Return(self.eval_expression(expr)?)),
       Statement::Expression(expr) => self.eval_expression(expr),
+      // The parser only allows these inside a loop body, so the loop is always there to catch them
+      Statement::Break(value, _) => {
+        let value = match value {
+          Some(expr) => self.eval_expression(expr)?,
+          None => Object::NoOp,
+        };
+        Err(Interrupt::Break(value))
+      }
+      Statement::Continue(_) => Err(Interrupt::Continue),
       _ => Ok(Object::NoOp),
     }
   }
 
   fn eval_expression(&mut self, expr: Expression) -> EvalResult {
+    let span = expr.span();
+    return self
+      .eval_unspanned_expression(expr)
+      .map_err(|interrupt| interrupt.at(span));
+  }
+
+  fn eval_unspanned_expression(&mut self, expr: Expression) -> EvalResult {
     match expr {
-      Expression::Ident(Token::IDENT(ident)) => match self.env.borrow().get(&ident) {
-        Some(val) => Ok(val),
-        None => Interrupt::error(format!("Invalid variable name {}", ident)),
-      },
-      Expression::Integer(Token::INT(num)) => Ok(Object::Integer(num)),
-      Expression::Prefix(op, expr) => {
+      Expression::Ident(Token::IDENT(ident), _) => {
+        match self
+          .env
+          .borrow()
+          .get(&ident)
+          .or_else(|| builtins::get(&ident))
+        {
+          Some(val) => Ok(val),
+          None => Interrupt::error(format!("Invalid variable name {}", ident)),
+        }
+      }
+      Expression::Integer(Token::INT(num), _) => Ok(Object::Integer(num)),
+      Expression::Integer(Token::BIGINT(num), _) => Ok(Object::BigInt(num)),
+      Expression::Float(Token::FLOAT(num), _) => Ok(Object::Float(num)),
+      Expression::String(Token::STRING(val), _) => Ok(Object::String(val)),
+      Expression::Prefix(op, expr, _) => {
         let expr = self.eval_expression(*expr)?;
         self.eval_prefix_expression(op, expr)
       }
-      Expression::Infix(op, left, right) => {
+      Expression::Infix(op @ (Token::AND | Token::OR), left, right, _) => {
+        self.eval_logical_expression(op, *left, *right)
+      }
+      Expression::Infix(op, left, right, _) => {
         let left = self.eval_expression(*left)?;
         let right = self.eval_expression(*right)?;
         self.eval_infix_expression(op, left, right)
       }
-      Expression::Boolean(Token::TRUE) => Ok(Object::Boolean(true)),
-      Expression::Boolean(Token::FALSE) => Ok(Object::Boolean(false)),
-      Expression::If(cond, consq, alt) => self.eval_if_expression(cond, consq, alt),
-      Expression::Function(params, body) => Ok(Object::Function(params, body)),
-      Expression::Call(func, args) => self.eval_call_expression(func, args),
+      Expression::Boolean(Token::TRUE, _) => Ok(Object::Boolean(true)),
+      Expression::Boolean(Token::FALSE, _) => Ok(Object::Boolean(false)),
+      Expression::If(cond, consq, alt, _) => self.eval_if_expression(*cond, consq, alt),
+      Expression::While(cond, body, _) => self.eval_while_expression(*cond, body),
+      Expression::For(Token::IDENT(var), collection, body, _) => {
+        self.eval_for_expression(var, *collection, body)
+      }
+      Expression::Function(params, body, _) => Ok(Object::Function(params, body, self.env.clone())),
+      Expression::Call(func, args, _) => self.eval_call_expression(*func, args),
+      Expression::Array(elements, _) => {
+        let elements = elements
+          .into_iter()
+          .map(|element| self.eval_expression(element))
+          .collect::<Result<Vec<Object>, Interrupt>>()?;
+        Ok(Object::Array(elements))
+      }
+      Expression::Hash(pairs, _) => {
+        let mut hash = BTreeMap::new();
+        for (key, value) in pairs {
+          let key = self.eval_expression(key)?;
+          let key = match key.hash_key() {
+            Some(key) => key,
+            None => return unusable_hash_key(&key),
+          };
+          hash.insert(key, self.eval_expression(value)?);
+        }
+        Ok(Object::Hash(hash))
+      }
+      Expression::Index(left, index, _) => {
+        let left = self.eval_expression(*left)?;
+        let index = self.eval_expression(*index)?;
+        self.eval_index_expression(left, index)
+      }
+      Expression::Assign(op, Token::IDENT(ident), value, _) => {
+        self.eval_assign_expression(op, ident, *value)
+      }
       _ => Interrupt::error("Invalid Expression".to_string()),
     }
   }
 
-  fn eval_call_expression(&mut self, func: Box<Expression>, args: Vec<Expression>) -> EvalResult {
-    if let Object::Function(params, body) = self.eval_expression(*func)? {
-      let mut enclosed_env = Environment::new_with_outer(self.env.clone());
+  fn eval_call_expression(&mut self, func: Expression, args: Vec<Expression>) -> EvalResult {
+    // Every call is a chance to notice Ctrl-C, so runaway recursion can be stopped
+    check_interrupted()?;
+
+    let func = self.eval_expression(func)?;
+    if let Object::Builtin(builtin) = func {
+      let args = args
+        .into_iter()
+        .map(|arg| self.eval_expression(arg))
+        .collect::<Result<Vec<Object>, Interrupt>>()?;
+      return builtin(args);
+    }
+
+    if let Object::Function(params, body, env) = func {
+      let mut enclosed_env = Environment::new_with_outer(env);
       for (param, arg) in params.iter().zip(args) {
         let param_name = match param {
-          Expression::Ident(Token::IDENT(inner)) => inner,
+          Expression::Ident(Token::IDENT(inner), _) => inner,
           _ => return Interrupt::error("Invalid variable name in function params".to_string()),
         };
         enclosed_env.insert(param_name.to_string(), self.eval_expression(arg)?);
       }
+      if self.depth >= self.max_depth {
+        return Interrupt::error(format!("Maximum call depth of {} exceeded", self.max_depth));
+      }
+
       let mut eval = Evaluator {
         env: Rc::new(RefCell::new(enclosed_env)),
+        depth: self.depth + 1,
+        max_depth: self.max_depth,
+      };
+      // A return statement only unwinds as far as the function it was called from
+      return match eval.eval_block(body) {
+        Err(Interrupt::Return(obj)) => Ok(obj),
+        res => res,
       };
-      return eval.eval_block(body.clone());
     }
 
     return Interrupt::error("Invalid function name/expression getting called".to_string());
   }
 
+  fn eval_index_expression(&mut self, left: Object, index: Object) -> EvalResult {
+    match (left, index) {
+      (Object::Array(elements), Object::Integer(idx)) => {
+        let len = elements.len() as isize;
+        // Negative indices count back from the end of the array
+        let pos = if idx < 0 { len + idx } else { idx };
+        if pos < 0 || pos >= len {
+          return Interrupt::error(format!(
+            "Index out of bounds: index {}, length {}",
+            idx, len
+          ));
+        }
+        Ok(elements[pos as usize].clone())
+      }
+      (Object::Array(elements), Object::BigInt(idx)) => Interrupt::error(format!(
+        "Index out of bounds: index {}, length {}",
+        idx,
+        elements.len()
+      )),
+      (Object::Hash(pairs), index) => {
+        let key = match index.hash_key() {
+          Some(key) => key,
+          None => return unusable_hash_key(&index),
+        };
+        match pairs.get(&key) {
+          Some(value) => Ok(value.clone()),
+          None => Interrupt::error(format!("Key not found in hash: {}", index)),
+        }
+      }
+      (Object::Array(_), index) => Interrupt::error(format!(
+        "Array index must be an INTEGER, got: {}",
+        index.type_name()
+      )),
+      (left, _) => Interrupt::error(format!(
+        "Index operator not supported on {}",
+        left.type_name()
+      )),
+    }
+  }
+
+  // Updates the binding in whichever scope declared it and evaluates to the new value
+  fn eval_assign_expression(&mut self, op: Token, ident: String, value: Expression) -> EvalResult {
+    let current = self.env.borrow().get(&ident);
+    let Some(current) = current else {
+      return Interrupt::error(format!("Cannot assign to undeclared variable {}", ident));
+    };
+
+    let value = self.eval_expression(value)?;
+    let value = match op {
+      Token::PLUSASSIGN => self.eval_infix_expression(Token::PLUS, current, value)?,
+      Token::MINUSASSIGN => self.eval_infix_expression(Token::MINUS, current, value)?,
+      Token::ASTERISKASSIGN => self.eval_infix_expression(Token::ASTERISK, current, value)?,
+      Token::SLASHASSIGN => self.eval_infix_expression(Token::SLASH, current, value)?,
+      _ => value,
+    };
+
+    self.env.borrow_mut().assign(&ident, value.clone());
+    return Ok(value);
+  }
+
+  // A `return` in a loop body leaves the surrounding function. A loop evaluates to the value it was
+  // broken out of with, if any.
+  fn eval_while_expression(&mut self, condition: Expression, body: BlockStatement) -> EvalResult {
+    loop {
+      check_interrupted()?;
+      if !self.eval_expression(condition.clone())?.is_truthy() {
+        return Ok(Object::NoOp);
+      }
+      if let Some(value) = self.eval_loop_body(body.clone())? {
+        return Ok(value);
+      }
+    }
+  }
+
+  fn eval_for_expression(
+    &mut self,
+    var: String,
+    collection: Expression,
+    body: BlockStatement,
+  ) -> EvalResult {
+    let span = collection.span();
+    let items: Box<dyn Iterator<Item = Object>> = match self.eval_expression(collection)? {
+      Object::Array(elements) => Box::new(elements.into_iter()),
+      Object::String(val) => Box::new(
+        val
+          .chars()
+          .map(|ch| Object::String(ch.to_string()))
+          .collect::<Vec<Object>>()
+          .into_iter(),
+      ),
+      Object::Hash(pairs) => Box::new(pairs.into_keys().map(Object::from)),
+      Object::Range(start, end, false) => Box::new((start..end).map(Object::Integer)),
+      Object::Range(start, end, true) => Box::new((start..=end).map(Object::Integer)),
+      other => {
+        return Interrupt::error(format!("Cannot iterate over {}", other.type_name()))
+          .map_err(|interrupt| interrupt.at(span))
+      }
+    };
+
+    for item in items {
+      check_interrupted()?;
+      // Every iteration gets its own scope for the loop variable, so it doesn't outlive the loop
+      let mut scope = Environment::new_with_outer(self.env.clone());
+      scope.insert(var.clone(), item);
+      let mut eval = Evaluator {
+        env: Rc::new(RefCell::new(scope)),
+        depth: self.depth,
+        max_depth: self.max_depth,
+      };
+      if let Some(value) = eval.eval_loop_body(body.clone())? {
+        return Ok(value);
+      }
+    }
+
+    return Ok(Object::NoOp);
+  }
+
+  // Runs one iteration of a loop, returns the value of a `break` once the loop should stop
+  fn eval_loop_body(&mut self, body: BlockStatement) -> Result<Option<Object>, Interrupt> {
+    match self.eval_block(body) {
+      Ok(_) | Err(Interrupt::Continue) => return Ok(None),
+      Err(Interrupt::Break(value)) => return Ok(Some(value)),
+      Err(interrupt) => return Err(interrupt),
+    }
+  }
+
   fn eval_if_expression(
     &mut self,
-    condition: Box<Expression>,
+    condition: Expression,
     consequence: BlockStatement,
     alt: Option<BlockStatement>,
   ) -> EvalResult {
-    let cond = self.eval_expression(*condition)?;
+    let cond = self.eval_expression(condition)?;
     if cond.is_truthy() {
       return self.eval_block(consequence);
     } else {
@@ -123,31 +339,194 @@ impl Evaluator {
 
   fn eval_infix_expression(&mut self, op: Token, left: Object, right: Object) -> EvalResult {
     match op {
-      Token::LT => Ok(Object::Boolean(left < right)),
-      Token::GT => Ok(Object::Boolean(left > right)),
-      Token::EQ => Ok(Object::Boolean(left == right)),
-      Token::NOTEQ => Ok(Object::Boolean(left != right)),
-      _ => {
-        let left_int = match left {
-          Object::Integer(num) => num,
-          _ => return Interrupt::error(format!("Left side of expression is not an int {}", left)),
-        };
-        let right_int = match right {
-          Object::Integer(num) => num,
-          _ => {
-            return Interrupt::error(format!("Right side of expression is not an int {}", right))
-          }
-        };
+      Token::EQ => return Ok(Object::Boolean(left == right)),
+      Token::NOTEQ => return Ok(Object::Boolean(left != right)),
+      Token::LT | Token::GT | Token::LTEQ | Token::GTEQ => {
+        // Unordered values (NaN) fail every comparison
+        let ordering = compare(&op, &left, &right)?;
+        return Ok(Object::Boolean(match op {
+          Token::LT => matches!(ordering, Some(Ordering::Less)),
+          Token::GT => matches!(ordering, Some(Ordering::Greater)),
+          Token::LTEQ => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+          _ => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+        }));
+      }
+      _ => (),
+    }
+
+    match (&left, &right) {
+      (Object::Integer(start), Object::Integer(end)) if op == Token::DOTDOT => {
+        return Ok(Object::Range(*start, *end, false))
+      }
+      (Object::Integer(start), Object::Integer(end)) if op == Token::DOTDOTEQ => {
+        return Ok(Object::Range(*start, *end, true))
+      }
+      (Object::BigInt(num), _) | (_, Object::BigInt(num))
+        if op == Token::DOTDOT || op == Token::DOTDOTEQ =>
+      {
+        return Interrupt::error(format!("Range bound too large: {}", num))
+      }
+      _ if op == Token::DOTDOT || op == Token::DOTDOTEQ => {
+        return Interrupt::error(format!(
+          "Range bounds must be INTEGER, got: {} {} {}",
+          left.type_name(),
+          op.symbol(),
+          right.type_name()
+        ))
+      }
+      (Object::String(left), Object::String(right)) => {
+        return self.eval_string_infix_expression(op, left, right)
+      }
+      (Object::String(_), _) | (_, Object::String(_)) => {
+        return Err(type_mismatch(&op, &left, &right))
+      }
+      _ => (),
+    }
+
+    if matches!(left, Object::Float(_)) || matches!(right, Object::Float(_)) {
+      return self.eval_float_infix_expression(op, left, right);
+    }
 
-        match op {
-          Token::PLUS => Ok(Object::Integer(left_int + right_int)),
-          Token::MINUS => Ok(Object::Integer(left_int - right_int)),
-          Token::ASTERISK => Ok(Object::Integer(left_int * right_int)),
-          Token::SLASH => Ok(Object::Integer(left_int / right_int)),
-          _ => Interrupt::error("Invalid infix operator!".to_string()),
+    let (left_int, right_int) = match (&left, &right) {
+      (Object::Integer(left), Object::Integer(right)) => (*left, *right),
+      _ => match (left.to_bigint(), right.to_bigint()) {
+        (Some(left), Some(right)) => return self.eval_bigint_infix_expression(op, left, right),
+        (None, _) => {
+          return Interrupt::error(format!("Left side of expression is not an int {}", left))
+        }
+        (_, None) => {
+          return Interrupt::error(format!("Right side of expression is not an int {}", right))
         }
-        // request_math_assistance(left, right, op)
+      },
+    };
+
+    // Fast path for results that fit in an isize, everything else (including the error cases) is
+    // left to the big integer path
+    let result = match op {
+      Token::PLUS => left_int.checked_add(right_int),
+      Token::MINUS => left_int.checked_sub(right_int),
+      Token::ASTERISK => left_int.checked_mul(right_int),
+      Token::SLASH => left_int.checked_div(right_int),
+      Token::PERCENT => left_int.checked_rem(right_int),
+      Token::POWER => u32::try_from(right_int)
+        .ok()
+        .and_then(|exp| left_int.checked_pow(exp)),
+      Token::AMPERSAND => Some(left_int & right_int),
+      Token::PIPE => Some(left_int | right_int),
+      Token::CARET => Some(left_int ^ right_int),
+      Token::SHL => u32::try_from(right_int)
+        .ok()
+        .and_then(|amount| left_int.checked_shl(amount))
+        .filter(|num| num >> right_int == left_int),
+      Token::SHR => u32::try_from(right_int)
+        .ok()
+        .and_then(|amount| left_int.checked_shr(amount)),
+      _ => return Interrupt::error("Invalid infix operator!".to_string()),
+    };
+
+    match result {
+      Some(num) => Ok(Object::Integer(num)),
+      None => {
+        self.eval_bigint_infix_expression(op, BigInt::from(left_int), BigInt::from(right_int))
+      }
+    }
+    // request_math_assistance(left, right, op)
+  }
+
+  // Integers mixed with floats are converted to the nearest float
+  fn eval_float_infix_expression(&mut self, op: Token, left: Object, right: Object) -> EvalResult {
+    let (left, right) = match (left.to_f64(), right.to_f64()) {
+      (Some(left), Some(right)) => (left, right),
+      (None, _) => {
+        return Interrupt::error(format!("Left side of expression is not a number {}", left))
+      }
+      (_, None) => {
+        return Interrupt::error(format!(
+          "Right side of expression is not a number {}",
+          right
+        ))
       }
+    };
+
+    match op {
+      Token::PLUS => Ok(Object::Float(left + right)),
+      Token::MINUS => Ok(Object::Float(left - right)),
+      Token::ASTERISK => Ok(Object::Float(left * right)),
+      Token::SLASH => Ok(Object::Float(left / right)),
+      Token::PERCENT => Ok(Object::Float(left % right)),
+      Token::POWER => Ok(Object::Float(left.powf(right))),
+      _ => Interrupt::error(format!("Invalid float operator: {}", op.symbol())),
+    }
+  }
+
+  fn eval_bigint_infix_expression(&mut self, op: Token, left: BigInt, right: BigInt) -> EvalResult {
+    let result = match op {
+      Token::PLUS => &left + &right,
+      Token::MINUS => &left - &right,
+      Token::ASTERISK => &left * &right,
+      Token::SLASH | Token::PERCENT => match left.checked_div_rem(&right) {
+        Some((quotient, _)) if op == Token::SLASH => quotient,
+        Some((_, remainder)) => remainder,
+        None => return Interrupt::error("Division by zero".to_string()),
+      },
+      Token::POWER if right.is_negative() => {
+        return Interrupt::error(format!("Negative exponent: {}", right))
+      }
+      Token::POWER => match right.to_isize().and_then(|exp| u32::try_from(exp).ok()) {
+        // The result has at least this many bits
+        Some(exp) if left.bits().saturating_sub(1) * exp as u64 > MAX_INTEGER_BITS => {
+          return Interrupt::error(format!("Result of {} ** {} is too large", left, right))
+        }
+        Some(exp) => left.pow(exp),
+        None => return Interrupt::error(format!("Exponent too large: {}", right)),
+      },
+      Token::AMPERSAND => &left & &right,
+      Token::PIPE => &left | &right,
+      Token::CARET => &left ^ &right,
+      Token::SHL | Token::SHR if right.is_negative() => {
+        return Interrupt::error(format!("Negative shift amount: {}", right))
+      }
+      Token::SHL | Token::SHR => match right
+        .to_isize()
+        .and_then(|amount| u32::try_from(amount).ok())
+      {
+        Some(amount)
+          if op == Token::SHL
+            && !left.is_zero()
+            && left.bits() + amount as u64 > MAX_INTEGER_BITS =>
+        {
+          return Interrupt::error(format!("Result of {} << {} is too large", left, right))
+        }
+        Some(amount) if op == Token::SHL => &left << amount,
+        Some(amount) => &left >> amount,
+        None => return Interrupt::error(format!("Shift amount too large: {}", right)),
+      },
+      _ => return Interrupt::error("Invalid infix operator!".to_string()),
+    };
+
+    return Ok(Object::from(result));
+  }
+
+  // The right operand is only evaluated when the left one doesn't already decide the result
+  fn eval_logical_expression(
+    &mut self,
+    op: Token,
+    left: Expression,
+    right: Expression,
+  ) -> EvalResult {
+    let left = self.eval_expression(left)?.is_truthy();
+    match (op, left) {
+      (Token::AND, false) | (Token::OR, true) => return Ok(Object::Boolean(left)),
+      _ => (),
+    }
+
+    return Ok(Object::Boolean(self.eval_expression(right)?.is_truthy()));
+  }
+
+  fn eval_string_infix_expression(&mut self, op: Token, left: &str, right: &str) -> EvalResult {
+    match op {
+      Token::PLUS => Ok(Object::String(format!("{}{}", left, right))),
+      _ => Interrupt::error(format!("Invalid string operator: {}", op.symbol())),
     }
   }
 
@@ -173,13 +552,23 @@ impl Evaluator {
     match op {
       Token::BANG => Ok(self.eval_bang_op(right)),
       Token::MINUS => self.eval_minus_op(right),
+      Token::TILDE => match right {
+        Object::Integer(num) => Ok(Object::Integer(!num)),
+        Object::BigInt(num) => Ok(Object::from(!&num)),
+        _ => Interrupt::error("Right side of ~ operator is not a valid integer".to_string()),
+      },
       _ => Interrupt::error("Invalid prefix operator".to_string()),
     }
   }
 
   fn eval_minus_op(&mut self, right: Object) -> EvalResult {
     match right {
-      Object::Integer(num) => Ok(Object::Integer(-num)),
+      Object::Integer(num) => match num.checked_neg() {
+        Some(num) => Ok(Object::Integer(num)),
+        None => Ok(Object::from(-BigInt::from(num))),
+      },
+      Object::BigInt(num) => Ok(Object::from(-num)),
+      Object::Float(num) => Ok(Object::Float(-num)),
       _ => Interrupt::error("Right side of - operator is not a valid integer".to_string()),
     }
   }
@@ -192,3 +581,50 @@ impl Evaluator {
     }
   }
 }
+
+fn check_interrupted() -> EvalResult {
+  if sigint::take() {
+    return Interrupt::error("Interrupted".to_string());
+  }
+
+  return Ok(Object::NoOp);
+}
+
+// Only numbers, strings and arrays of comparable elements have an order, values of other different
+// types are never ordered against each other. None when a NaN is involved.
+fn compare(op: &Token, left: &Object, right: &Object) -> Result<Option<Ordering>, Interrupt> {
+  match (left, right) {
+    _ if left.is_number() && right.is_number() => return Ok(cmp_numbers(left, right)),
+    (Object::String(a), Object::String(b)) => return Ok(Some(a.cmp(b))),
+    (Object::Array(a), Object::Array(b)) => {
+      for (a, b) in a.iter().zip(b) {
+        match compare(op, a, b)? {
+          Some(Ordering::Equal) => continue,
+          ordering => return Ok(ordering),
+        }
+      }
+      return Ok(Some(a.len().cmp(&b.len())));
+    }
+    _ if left.type_name() == right.type_name() => {
+      return Err(Interrupt::Error(RuntimeError::new(format!(
+        "Cannot order {} values with {}",
+        left.type_name(),
+        op.symbol()
+      ))))
+    }
+    _ => return Err(type_mismatch(op, left, right)),
+  }
+}
+
+fn type_mismatch(op: &Token, left: &Object, right: &Object) -> Interrupt {
+  return Interrupt::Error(RuntimeError::new(format!(
+    "Type mismatch: {} {} {}",
+    left.type_name(),
+    op.symbol(),
+    right.type_name()
+  )));
+}
+
+fn unusable_hash_key(key: &Object) -> EvalResult {
+  return Interrupt::error(format!("Unusable as hash key: {}", key.type_name()));
+}
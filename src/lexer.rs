@@ -1,4 +1,26 @@
-use crate::token::Token;
+use crate::{
+  bigint::BigInt,
+  token::{Span, Token},
+};
+
+// Reasons for the lexer to produce an ILLEGAL token
+#[derive(Debug)]
+pub enum LexerError {
+  UnterminatedString,
+  InvalidEscape,
+  // the literal as written
+  InvalidNumber(String),
+}
+
+impl LexerError {
+  pub fn message(&self) -> String {
+    match self {
+      LexerError::UnterminatedString => "Unterminated string".to_string(),
+      LexerError::InvalidEscape => "Invalid escape sequence in string".to_string(),
+      LexerError::InvalidNumber(literal) => format!("Invalid number literal: {}", literal),
+    }
+  }
+}
 
 #[derive(Debug)]
 pub struct Lexer<'a> {
@@ -6,15 +28,29 @@ pub struct Lexer<'a> {
   pos: usize,
   next_pos: usize,
   ch: u8,
+  // Position of `ch`, columns count characters rather than bytes
+  line: usize,
+  col: usize,
+  // Why the last token is ILLEGAL, if the lexer knows
+  error: Option<LexerError>,
 }
 
 impl<'a> Lexer<'a> {
   pub fn new(input: &'a str) -> Self {
+    return Lexer::starting_at(input, 0);
+  }
+
+  // Lexes `input` from the byte offset `start`, which must be the beginning of a line. Spans stay
+  // relative to the whole input so they can be rendered against it.
+  pub fn starting_at(input: &'a str, start: usize) -> Self {
     let mut lexer = Lexer {
       input,
-      pos: 0,
-      next_pos: 0,
+      pos: start,
+      next_pos: start,
       ch: 0,
+      line: 1 + input[..start].matches('\n').count(),
+      col: 0,
+      error: None,
     };
 
     lexer.read_char();
@@ -22,6 +58,11 @@ impl<'a> Lexer<'a> {
   }
 
   fn read_char(&mut self) {
+    if self.ch == b'\n' {
+      self.line += 1;
+      self.col = 0;
+    }
+
     self.ch = if self.next_pos >= self.input.len() {
       0
     } else {
@@ -29,9 +70,14 @@ impl<'a> Lexer<'a> {
     };
     self.pos = self.next_pos;
     self.next_pos += 1;
+
+    // UTF-8 continuation bytes belong to the character before them
+    if self.ch & 0xC0 != 0x80 {
+      self.col += 1;
+    }
   }
 
-  fn peek_char(&mut self) -> u8 {
+  fn peek_char(&self) -> u8 {
     if self.next_pos >= self.input.len() {
       return 0;
     } else {
@@ -39,14 +85,38 @@ impl<'a> Lexer<'a> {
     }
   }
 
+  // Takes the error explaining the ILLEGAL token that was just returned
+  pub fn take_error(&mut self) -> Option<LexerError> {
+    return self.error.take();
+  }
+
+  fn illegal(&mut self, error: LexerError) -> Token {
+    self.error = Some(error);
+    return Token::ILLEGAL;
+  }
+
   fn skip_whitespace(&mut self) {
     while self.ch == b' ' || self.ch == b'\t' || self.ch == b'\n' || self.ch == b'\r' {
       self.read_char();
     }
   }
 
-  pub fn next_token(&mut self) -> Token {
+  pub fn next_token(&mut self) -> (Token, Span) {
     self.skip_whitespace();
+    let start = self.pos.min(self.input.len());
+    let (line, col) = (self.line, self.col);
+    let token = self.read_token();
+    let span = Span {
+      start,
+      end: self.pos.min(self.input.len()),
+      line,
+      col,
+    };
+
+    return (token, span);
+  }
+
+  fn read_token(&mut self) -> Token {
     let token = match self.ch {
       b'=' => {
         if self.peek_char() == b'=' {
@@ -57,11 +127,12 @@ impl<'a> Lexer<'a> {
         }
       }
       b';' => Token::SEMICOLON,
+      b':' => Token::COLON,
       b'(' => Token::LPAREN,
       b')' => Token::RPAREN,
       b',' => Token::COMMA,
-      b'+' => Token::PLUS,
-      b'-' => Token::MINUS,
+      b'+' => self.read_operator(Token::PLUS, Token::PLUSASSIGN),
+      b'-' => self.read_operator(Token::MINUS, Token::MINUSASSIGN),
       b'!' => {
         if self.peek_char() == b'=' {
           self.read_char();
@@ -70,22 +141,79 @@ impl<'a> Lexer<'a> {
           Token::BANG
         }
       }
-      b'*' => Token::ASTERISK,
-      b'/' => Token::SLASH,
-      b'<' => Token::LT,
-      b'>' => Token::GT,
+      b'*' if self.peek_char() == b'*' => {
+        self.read_char();
+        Token::POWER
+      }
+      b'*' => self.read_operator(Token::ASTERISK, Token::ASTERISKASSIGN),
+      b'/' => self.read_operator(Token::SLASH, Token::SLASHASSIGN),
+      // Outside of ranges a dot only belongs in a number, as in `.5` missing its leading digit
+      b'.' if self.peek_char() != b'.' => {
+        let start = self.pos;
+        self.read_char();
+        self.read_digits();
+        return self.invalid_number(start);
+      }
+      b'.' => {
+        self.read_char();
+        self.read_operator(Token::DOTDOT, Token::DOTDOTEQ)
+      }
+      b'%' => Token::PERCENT,
+      b'~' => Token::TILDE,
+      b'^' => Token::CARET,
+      b'&' => self.read_pair(b'&', Token::AND, Token::AMPERSAND),
+      b'|' => self.read_pair(b'|', Token::OR, Token::PIPE),
+      b'<' if self.peek_char() == b'<' => {
+        self.read_char();
+        Token::SHL
+      }
+      b'>' if self.peek_char() == b'>' => {
+        self.read_char();
+        Token::SHR
+      }
+      b'<' => self.read_operator(Token::LT, Token::LTEQ),
+      b'>' => self.read_operator(Token::GT, Token::GTEQ),
       b'}' => Token::RBRACE,
       b'{' => Token::LBRACE,
+      b'[' => Token::LBRACKET,
+      b']' => Token::RBRACKET,
+      b'"' => return self.read_string(),
       ch if ch.is_ascii_alphabetic() || ch == b'_' => return self.read_identifier(),
       ch if ch.is_ascii_digit() => return self.read_number(),
       0 => Token::EOF,
-      _ => Token::ILLEGAL,
+      _ => {
+        // Take the whole character, not just its first byte, so spans stay on character boundaries
+        while self.peek_char() & 0xC0 == 0x80 {
+          self.read_char();
+        }
+        Token::ILLEGAL
+      }
     };
 
     self.read_char();
     return token;
   }
 
+  // Operators that double up into another operator, e.g. `&` and `&&`
+  fn read_pair(&mut self, ch: u8, pair: Token, single: Token) -> Token {
+    if self.peek_char() == ch {
+      self.read_char();
+      return pair;
+    }
+
+    return single;
+  }
+
+  // Operators that have a form ending in `=`, e.g. `+` and `+=`
+  fn read_operator(&mut self, op: Token, assign: Token) -> Token {
+    if self.peek_char() == b'=' {
+      self.read_char();
+      return assign;
+    }
+
+    return op;
+  }
+
   fn read_identifier(&mut self) -> Token {
     let start = self.pos;
     while self.ch.is_ascii_alphabetic() || self.ch == b'_' {
@@ -102,17 +230,211 @@ impl<'a> Lexer<'a> {
       "if" => Token::IF,
       "else" => Token::ELSE,
       "return" => Token::RETURN,
+      "while" => Token::WHILE,
+      "for" => Token::FOR,
+      "in" => Token::IN,
+      "break" => Token::BREAK,
+      "continue" => Token::CONTINUE,
       _ => Token::IDENT(identifier.to_string()),
     }
   }
 
+  fn read_string(&mut self) -> Token {
+    let mut bytes: Vec<u8> = vec![];
+    // A bad escape doesn't end the literal, the rest is still read so it isn't mistaken for code
+    let mut error = None;
+    self.read_char();
+
+    loop {
+      match self.ch {
+        b'"' => break,
+        0 if self.pos >= self.input.len() => return self.illegal(LexerError::UnterminatedString),
+        b'\\' => {
+          self.read_char();
+          let escaped = match self.ch {
+            b'n' => Some('\n'),
+            b't' => Some('\t'),
+            b'"' => Some('"'),
+            b'\\' => Some('\\'),
+            b'u' => self.read_unicode_escape(),
+            _ => None,
+          };
+          match escaped {
+            Some(ch) => {
+              let mut buf = [0; 4];
+              bytes.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
+            }
+            None => {
+              error.get_or_insert(LexerError::InvalidEscape);
+              // A broken `\u{` escape can stop on the closing quote, which still ends the string
+              if self.ch == b'"' || self.ch == 0 {
+                continue;
+              }
+            }
+          }
+        }
+        ch => bytes.push(ch),
+      }
+      self.read_char();
+    }
+
+    // Skip the closing quote
+    self.read_char();
+    if let Some(error) = error {
+      return self.illegal(error);
+    }
+    // Only whole escapes and bytes copied from the (valid UTF-8) input end up in the buffer
+    return Token::STRING(String::from_utf8(bytes).unwrap());
+  }
+
+  // Reads the `{XXXX}` part of a `\u{XXXX}` escape, leaving the lexer on the closing brace
+  fn read_unicode_escape(&mut self) -> Option<char> {
+    self.read_char();
+    if self.ch != b'{' {
+      return None;
+    }
+
+    self.read_char();
+    let start = self.pos;
+    while self.ch.is_ascii_hexdigit() {
+      self.read_char();
+    }
+
+    if self.ch != b'}' || start == self.pos {
+      return None;
+    }
+
+    let code = u32::from_str_radix(&self.input[start..self.pos], 16).ok()?;
+    return char::from_u32(code);
+  }
+
+  fn read_digits(&mut self) {
+    while self.ch.is_ascii_digit() || self.ch == b'_' {
+      self.read_char();
+    }
+  }
+
   fn read_number(&mut self) -> Token {
     let start = self.pos;
-    while self.ch.is_ascii_digit() {
+    match (self.ch, self.peek_char()) {
+      (b'0', b'x') => return self.read_radix_number(start, 16),
+      (b'0', b'o') => return self.read_radix_number(start, 8),
+      (b'0', b'b') => return self.read_radix_number(start, 2),
+      _ => (),
+    }
+
+    self.read_digits();
+
+    // A dot only starts a fraction when a digit follows, so `1..5` stays a range
+    let mut is_float = false;
+    if self.ch == b'.' && self.peek_char().is_ascii_digit() {
+      is_float = true;
+      self.read_char();
+      self.read_digits();
+    } else if self.ch == b'.' && self.peek_char() != b'.' {
       self.read_char();
+      return self.invalid_number(start);
+    }
+    if self.ch == b'e' || self.ch == b'E' {
+      is_float = true;
+      self.read_char();
+      if self.ch == b'+' || self.ch == b'-' {
+        self.read_char();
+      }
+      if !self.ch.is_ascii_digit() {
+        return self.invalid_number(start);
+      }
+      self.read_digits();
+    }
+
+    let literal = &self.input[start..self.pos];
+    if !has_valid_separators(literal, 10) {
+      return self.invalid_number(start);
     }
 
-    let digit = &self.input[start..self.pos];
-    return Token::INT(digit.parse::<isize>().unwrap());
+    let digits = literal.replace('_', "");
+    if is_float {
+      // Anything Rust can't parse was rejected above, literals too large for a float are infinite
+      return Token::FLOAT(digits.parse::<f64>().unwrap());
+    }
+    return integer_token(&digits, 10);
+  }
+
+  // Reads a `0x`, `0o` or `0b` literal. Everything alphanumeric after the prefix belongs to it, so
+  // `0b102` is reported as malformed instead of being lexed as `0b10` followed by `2`.
+  fn read_radix_number(&mut self, start: usize, radix: u32) -> Token {
+    self.read_char();
+    self.read_char();
+    let digits_start = self.pos;
+    while self.ch.is_ascii_alphanumeric() || self.ch == b'_' {
+      self.read_char();
+    }
+
+    let literal = &self.input[digits_start..self.pos];
+    if literal.is_empty()
+      || !has_valid_separators(literal, radix)
+      || !literal.chars().all(|ch| ch == '_' || ch.is_digit(radix))
+    {
+      return self.invalid_number(start);
+    }
+
+    return integer_token(&literal.replace('_', ""), radix);
+  }
+
+  fn invalid_number(&mut self, start: usize) -> Token {
+    let literal = self.input[start..self.pos].to_string();
+    return self.illegal(LexerError::InvalidNumber(literal));
+  }
+}
+
+// Underscores may only separate two digits, as in `1_000_000`
+fn has_valid_separators(literal: &str, radix: u32) -> bool {
+  let chars: Vec<char> = literal.chars().collect();
+  return chars.iter().enumerate().all(|(i, ch)| {
+    *ch != '_'
+      || (i > 0
+        && i + 1 < chars.len()
+        && chars[i - 1].is_digit(radix)
+        && chars[i + 1].is_digit(radix))
+  });
+}
+
+// `digits` must only contain valid digits for `radix`
+fn integer_token(digits: &str, radix: u32) -> Token {
+  match isize::from_str_radix(digits, radix) {
+    Ok(num) => return Token::INT(num),
+    Err(_) => return Token::BIGINT(BigInt::from_str_radix(digits, radix).unwrap()),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{Lexer, LexerError};
+  use crate::token::Token;
+
+  fn lex(input: &str) -> Vec<(Token, Option<LexerError>)> {
+    let mut lexer = Lexer::new(input);
+    let mut tokens = vec![];
+    loop {
+      let (token, _) = lexer.next_token();
+      if token == Token::EOF {
+        return tokens;
+      }
+      tokens.push((token, lexer.take_error()));
+    }
+  }
+
+  #[test]
+  fn invalid_escapes_consume_the_whole_string() {
+    let tokens = lex(r#""tab\q here" "\u{110000}" "\u{12" "ok""#);
+    assert!(matches!(
+      tokens.as_slice(),
+      [
+        (Token::ILLEGAL, Some(LexerError::InvalidEscape)),
+        (Token::ILLEGAL, Some(LexerError::InvalidEscape)),
+        (Token::ILLEGAL, Some(LexerError::InvalidEscape)),
+        (Token::STRING(ok), None),
+      ] if ok == "ok"
+    ));
   }
 }
@@ -1,51 +1,182 @@
+#![allow(clippy::needless_return, clippy::upper_case_acronyms)]
+
 mod ast;
+mod bigint;
+mod builtins;
+mod diagnostics;
+mod editor;
 mod env;
 mod eval;
 mod lexer;
 mod minions;
 mod object;
 mod parser;
+mod repl;
+mod sigint;
 mod token;
 
-use std::io::{self, Write};
+use std::{fs, io, process::ExitCode, rc::Rc};
 
+use diagnostics::Diagnostic;
 use eval::Evaluator;
 use lexer::Lexer;
-use object::Interrupt;
+use minions::MinionRoster;
+use object::{Interrupt, Object};
 use parser::Parser;
 
-fn main() {
-  let mut eval = Evaluator::new();
-
-  println!("Welcome to the Minion REPL 🍌🍌🍌🍌");
-  println!("-----------------------------------");
-
-  loop {
-    let mut input = String::new();
-    print!(">>> ");
-    io::stdout().flush().unwrap();
-    io::stdin()
-      .read_line(&mut input)
-      .expect("Failed to read repl line");
-
-    let mut parser = Parser::new(Lexer::new(&input));
-    let program = parser.parse_program();
-
-    if parser.errors.len() > 0 {
-      // TODO: Add support for block expressions
-      for error in parser.errors {
-        match error {
-          parser::ParserError::UnexpectedToken(_, _) => println!("{}", error),
-          parser::ParserError::UnknownPrefix(_) => println!("{}", error),
-          parser::ParserError::InvalidIdent(_) => println!("{}", error),
-        }
+const USAGE: &str = "Usage: minion [options] [script.mn [args...] | -e <code> [args...]]
+
+Options:
+  --no-color          Print diagnostics without ANSI colors
+  --roster <file>     Only accept identifiers using the names in <file>, one per line
+  --exact-names       Identifiers must be exactly a roster name instead of containing one
+  --case-sensitive    Match identifiers against the roster case sensitively";
+
+// Settings shared by the REPL and script runs
+pub struct Config {
+  pub color: bool,
+  pub roster: Rc<MinionRoster>,
+  // Nested function calls allowed before evaluation stops with an error
+  pub max_call_depth: usize,
+}
+
+// The evaluator recurses for every nested call, give it room for `MAX_CALL_DEPTH` calls
+const STACK_SIZE: usize = 1024 * 1024 * 1024;
+// Deep enough for any sensible recursion, shallow enough to fit in `STACK_SIZE`
+const MAX_CALL_DEPTH: usize = 5_000;
+// What safely fits in a typical 8 MiB main thread stack, even in debug builds
+const FALLBACK_CALL_DEPTH: usize = 100;
+
+fn main() -> ExitCode {
+  // Ctrl-C has to reach the interpreter thread. Handled by the main thread sitting in `join`, it
+  // would never interrupt a read at the REPL prompt.
+  sigint::block();
+  let spawned = std::thread::Builder::new()
+    .stack_size(STACK_SIZE)
+    .spawn(|| {
+      sigint::unblock();
+      return start(MAX_CALL_DEPTH);
+    });
+
+  return match spawned {
+    Ok(thread) => thread.join().unwrap_or(ExitCode::FAILURE),
+    // Run on the main thread's smaller stack rather than not at all
+    Err(_) => {
+      sigint::unblock();
+      start(FALLBACK_CALL_DEPTH)
+    }
+  };
+}
+
+fn start(max_call_depth: usize) -> ExitCode {
+  let args: Vec<String> = std::env::args().skip(1).collect();
+  let mut color = true;
+  let mut roster = MinionRoster::default();
+  let (mut exact, mut case_sensitive) = (false, false);
+
+  let mut idx = 0;
+  while let Some(arg) = args.get(idx) {
+    match arg.as_str() {
+      "--no-color" => color = false,
+      "--exact-names" => exact = true,
+      "--case-sensitive" => case_sensitive = true,
+      "--roster" => {
+        idx += 1;
+        let Some(path) = args.get(idx) else {
+          eprintln!("minion: --roster requires a file\n{}", USAGE);
+          return ExitCode::from(2);
+        };
+        roster = match MinionRoster::from_file(path) {
+          Ok(roster) => roster,
+          Err(err) => {
+            eprintln!("minion: cannot read roster {}: {}", path, err);
+            return ExitCode::FAILURE;
+          }
+        };
+      }
+      _ => break,
+    }
+    idx += 1;
+  }
+
+  let args = &args[idx..];
+  // The REPL prints diagnostics along with its other output, scripts print them to stderr
+  let color = color
+    && match args.first() {
+      None => diagnostics::color_default(io::stdout()),
+      Some(_) => diagnostics::color_default(io::stderr()),
+    };
+  let config = Config {
+    color,
+    roster: Rc::new(roster.exact(exact).case_sensitive(case_sensitive)),
+    max_call_depth,
+  };
+
+  match args.first().map(String::as_str) {
+    None => repl::start(&config),
+    Some("-h") | Some("--help") => {
+      println!("{}", USAGE);
+      ExitCode::SUCCESS
+    }
+    Some("-e") => match args.get(1) {
+      Some(code) => run("<eval>", code, &args[2..], true, &config),
+      None => {
+        eprintln!("minion: -e requires an argument\n{}", USAGE);
+        ExitCode::from(2)
       }
-    } else {
-      match eval.eval(program) {
-        Ok(object::Object::NoOp) => (),
-        Ok(obj) | Err(Interrupt::Return(obj)) => println!("{}", obj),
-        Err(Interrupt::Error(err)) => println!("{}", err),
+    },
+    Some(flag) if flag.starts_with('-') => {
+      eprintln!("minion: unknown option {}\n{}", flag, USAGE);
+      ExitCode::from(2)
+    }
+    Some(path) => match fs::read_to_string(path) {
+      Ok(source) => run(path, &source, &args[1..], false, &config),
+      Err(err) => {
+        eprintln!("minion: cannot read {}: {}", path, err);
+        ExitCode::FAILURE
+      }
+    },
+  }
+}
+
+// Evaluates a whole source file, the remaining command line arguments are bound to `args`
+fn run(name: &str, source: &str, args: &[String], print_result: bool, config: &Config) -> ExitCode {
+  let mut parser = Parser::new(Lexer::new(source), config.roster.clone());
+  let program = parser.parse_program();
+
+  if !parser.errors.is_empty() {
+    for error in &parser.errors {
+      eprint!(
+        "{}",
+        Diagnostic::from(error).render(name, source, config.color)
+      );
+    }
+    return ExitCode::FAILURE;
+  }
+
+  let mut eval = Evaluator::new(config.max_call_depth);
+  let args = args.iter().map(|arg| Object::String(arg.clone())).collect();
+  eval
+    .env
+    .borrow_mut()
+    .insert("args".to_string(), Object::Array(args));
+
+  match eval.eval(program) {
+    Ok(obj) | Err(Interrupt::Return(obj)) => {
+      if print_result && obj != Object::NoOp {
+        println!("{}", obj);
       }
+      ExitCode::SUCCESS
+    }
+    Err(Interrupt::Exit(code)) => ExitCode::from(code),
+    // The parser rejects loop control outside of a loop
+    Err(Interrupt::Break(_) | Interrupt::Continue) => unreachable!(),
+    Err(Interrupt::Error(err)) => {
+      eprint!(
+        "{}",
+        Diagnostic::from(&err).render(name, source, config.color)
+      );
+      ExitCode::FAILURE
     }
   }
 }
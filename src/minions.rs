@@ -1,5 +1,9 @@
+use std::{fs, io, path::Path};
+
 use crate::token::Token;
 
+const MAX_SUGGESTIONS: usize = 3;
+
 const MINIONS: [&str; 165] = [
   "mel",
   "kevin",
@@ -168,12 +172,167 @@ const MINIONS: [&str; 165] = [
   "ziggy",
 ];
 
-pub fn is_valid_minion(ident: &Token) -> bool {
-  match ident {
-    Token::IDENT(name) => {
-      let name = name.to_lowercase();
-      MINIONS.iter().any(|&minion| name.contains(minion))
+// The names identifiers are checked against. By default an identifier is valid when it contains any
+// roster name, ignoring case.
+#[derive(Debug, Clone)]
+pub struct MinionRoster {
+  names: Vec<String>,
+  exact: bool,
+  case_sensitive: bool,
+}
+
+impl Default for MinionRoster {
+  fn default() -> Self {
+    MinionRoster::new(MINIONS.iter().map(|name| name.to_string()).collect())
+  }
+}
+
+impl MinionRoster {
+  pub fn new(names: Vec<String>) -> Self {
+    MinionRoster {
+      names,
+      exact: false,
+      case_sensitive: false,
+    }
+  }
+
+  // Reads one name per line, blank lines and lines starting with `#` are skipped
+  pub fn from_file(path: impl AsRef<Path>) -> io::Result<Self> {
+    let names: Vec<String> = fs::read_to_string(path)?
+      .lines()
+      .map(str::trim)
+      .filter(|line| !line.is_empty() && !line.starts_with('#'))
+      .map(String::from)
+      .collect();
+
+    if names.is_empty() {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "roster contains no names",
+      ));
+    }
+
+    return Ok(MinionRoster::new(names));
+  }
+
+  // Only accept identifiers that are exactly a roster name rather than containing one
+  pub fn exact(mut self, exact: bool) -> Self {
+    self.exact = exact;
+    return self;
+  }
+
+  pub fn is_exact(&self) -> bool {
+    return self.exact;
+  }
+
+  pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+    self.case_sensitive = case_sensitive;
+    return self;
+  }
+
+  pub fn names(&self) -> &[String] {
+    return &self.names;
+  }
+
+  pub fn is_valid(&self, ident: &Token) -> bool {
+    match ident {
+      Token::IDENT(name) => {
+        let name = self.normalize(name);
+        self.names.iter().any(|minion| {
+          let minion = self.normalize(minion);
+          if self.exact {
+            name == minion
+          } else {
+            name.contains(&minion)
+          }
+        })
+      }
+      _ => false,
+    }
+  }
+
+  // Valid identifiers close to `name`, best match first. Each `_` separated part of the name is
+  // checked against the roster for typos, if none are close a minion is tacked onto the name instead.
+  // Typos are found ignoring case, so a name in the wrong case is corrected even when matching is
+  // case sensitive.
+  pub fn suggestions(&self, name: &str) -> Vec<String> {
+    // With exact matching only a whole roster name will do
+    let parts: Vec<&str> = if self.exact {
+      vec![name]
+    } else {
+      name.split('_').collect()
+    };
+    let mut candidates: Vec<(usize, String)> = vec![];
+
+    for (i, part) in parts.iter().enumerate() {
+      let part = part.to_lowercase();
+      let len = part.chars().count();
+      let max_distance = (len / 3).max(1);
+
+      for minion in &self.names {
+        let distance = edit_distance(&part, &minion.to_lowercase());
+        if distance <= max_distance && distance < len {
+          let mut fixed = parts.clone();
+          fixed[i] = minion;
+          candidates.push((distance, fixed.join("_")));
+        }
+      }
+    }
+
+    candidates.sort();
+    let mut suggestions: Vec<String> = vec![];
+    for (_, candidate) in candidates {
+      if !suggestions.contains(&candidate) {
+        suggestions.push(candidate);
+      }
+    }
+
+    if suggestions.is_empty() && !self.exact {
+      if let Some((first, second)) = self.favourites() {
+        suggestions.push(format!("{}_{}", name, first));
+        suggestions.push(format!("{}_{}", second, name));
+      }
+    }
+
+    suggestions.truncate(MAX_SUGGESTIONS);
+    return suggestions;
+  }
+
+  // Minions to tack onto a name when nothing in the roster is close, kevin and bob when available.
+  // None for an empty roster.
+  fn favourites(&self) -> Option<(&str, &str)> {
+    let find = |favourite: &str| {
+      self
+        .names
+        .iter()
+        .find(|name| self.normalize(name) == favourite)
+    };
+    let first = find("kevin").or(self.names.first())?;
+    let second = find("bob").unwrap_or(self.names.get(1).unwrap_or(first));
+    return Some((first, second));
+  }
+
+  fn normalize(&self, name: &str) -> String {
+    if self.case_sensitive {
+      name.to_string()
+    } else {
+      name.to_lowercase()
     }
-    _ => false
   }
 }
+
+fn edit_distance(a: &str, b: &str) -> usize {
+  let b: Vec<char> = b.chars().collect();
+  let mut prev: Vec<usize> = (0..=b.len()).collect();
+
+  for (i, a_ch) in a.chars().enumerate() {
+    let mut curr = vec![i + 1; b.len() + 1];
+    for (j, b_ch) in b.iter().enumerate() {
+      let cost = if a_ch == *b_ch { 0 } else { 1 };
+      curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+    }
+    prev = curr;
+  }
+
+  return prev[b.len()];
+}
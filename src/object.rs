@@ -1,43 +1,271 @@
 use core::fmt;
+use std::{cell::RefCell, cmp::Ordering, collections::BTreeMap, rc::Rc};
 
-use crate::ast::{BlockStatement, Expression};
+use crate::{
+  ast::{BlockStatement, Expression},
+  bigint::BigInt,
+  env::Environment,
+  token::Span,
+};
 
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[derive(Debug, Clone)]
 pub enum Object {
   Integer(isize),
+  // Integers that don't fit in an isize, arithmetic moves between the two transparently
+  BigInt(BigInt),
+  Float(f64),
   Boolean(bool),
-  Function(Vec<Expression>, BlockStatement),
+  String(String),
+  Array(Vec<Object>),
+  Hash(BTreeMap<HashKey, Object>),
+  // start, end, whether the end is included
+  Range(isize, isize, bool),
+  // params, body, captured environment
+  Function(Vec<Expression>, BlockStatement, Rc<RefCell<Environment>>),
+  Builtin(fn(Vec<Object>) -> EvalResult),
   NoOp,
 }
 
+// Structural equality, values of different types are never equal except for numbers, which are
+// compared by value. NaN is not equal to anything, itself included.
+impl PartialEq for Object {
+  fn eq(&self, other: &Self) -> bool {
+    match (self, other) {
+      (Object::Float(_), _) | (_, Object::Float(_)) if self.is_number() && other.is_number() => {
+        cmp_numbers(self, other) == Some(Ordering::Equal)
+      }
+      (Object::Integer(a), Object::Integer(b)) => a == b,
+      // Never equal to an Integer, a value that fits in an isize is never stored as a BigInt
+      (Object::BigInt(a), Object::BigInt(b)) => a == b,
+      (Object::Boolean(a), Object::Boolean(b)) => a == b,
+      (Object::String(a), Object::String(b)) => a == b,
+      (Object::Array(a), Object::Array(b)) => a == b,
+      (Object::Hash(a), Object::Hash(b)) => a == b,
+      (Object::Range(a_start, a_end, a_incl), Object::Range(b_start, b_end, b_incl)) => {
+        a_start == b_start && a_end == b_end && a_incl == b_incl
+      }
+      // Two closures are only equal if they are the same literal captured in the same scope
+      (Object::Function(a_params, a_body, a_env), Object::Function(b_params, b_body, b_env)) => {
+        a_params == b_params && a_body == b_body && Rc::ptr_eq(a_env, b_env)
+      }
+      (Object::Builtin(a), Object::Builtin(b)) => std::ptr::fn_addr_eq(*a, *b),
+      (Object::NoOp, Object::NoOp) => true,
+      _ => false,
+    }
+  }
+}
+
 impl Object {
-  pub fn is_truthy(&self) -> bool {
+  pub fn type_name(&self) -> &'static str {
+    match self {
+      Object::Integer(_) | Object::BigInt(_) => "INTEGER",
+      Object::Float(_) => "FLOAT",
+      Object::Boolean(_) => "BOOLEAN",
+      Object::String(_) => "STRING",
+      Object::Array(_) => "ARRAY",
+      Object::Hash(_) => "HASH",
+      Object::Range(_, _, _) => "RANGE",
+      Object::Function(_, _, _) => "FUNCTION",
+      Object::Builtin(_) => "BUILTIN",
+      Object::NoOp => "NOOP",
+    }
+  }
+
+  pub fn hash_key(&self) -> Option<HashKey> {
+    match self {
+      Object::Integer(num) => Some(HashKey::Integer(*num)),
+      Object::BigInt(num) => Some(HashKey::BigInt(num.clone())),
+      Object::Boolean(val) => Some(HashKey::Boolean(*val)),
+      Object::String(val) => Some(HashKey::String(val.clone())),
+      _ => None,
+    }
+  }
+
+  pub fn to_bigint(&self) -> Option<BigInt> {
     match self {
-      Object::Boolean(false) => false,
-      _ => true,
+      Object::Integer(num) => Some(BigInt::from(*num)),
+      Object::BigInt(num) => Some(num.clone()),
+      _ => None,
     }
   }
+
+  pub fn to_f64(&self) -> Option<f64> {
+    match self {
+      Object::Integer(num) => Some(*num as f64),
+      Object::BigInt(num) => Some(num.to_f64()),
+      Object::Float(num) => Some(*num),
+      _ => None,
+    }
+  }
+
+  pub fn is_number(&self) -> bool {
+    return matches!(
+      self,
+      Object::Integer(_) | Object::BigInt(_) | Object::Float(_)
+    );
+  }
+
+  pub fn is_truthy(&self) -> bool {
+    !matches!(self, Object::Boolean(false))
+  }
 }
 
 impl fmt::Display for Object {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     match self {
       Object::Integer(num) => write!(f, "{}", num),
+      Object::BigInt(num) => write!(f, "{}", num),
+      // Debug keeps the `.0` on whole numbers so they don't read as integers
+      Object::Float(num) => write!(f, "{:?}", num),
       Object::Boolean(val) => write!(f, "{}", val),
-      Object::Function(_params, _body) => write!(f, "[Function Object]"),
+      Object::String(val) => write!(f, "{}", val),
+      Object::Array(elements) => {
+        write!(f, "[")?;
+        for (i, element) in elements.iter().enumerate() {
+          if i > 0 {
+            write!(f, ", ")?;
+          }
+          write_nested(f, element)?;
+        }
+        write!(f, "]")
+      }
+      Object::Hash(pairs) => {
+        write!(f, "{{")?;
+        for (i, (key, value)) in pairs.iter().enumerate() {
+          if i > 0 {
+            write!(f, ", ")?;
+          }
+          write_nested(f, &Object::from(key.clone()))?;
+          write!(f, ": ")?;
+          write_nested(f, value)?;
+        }
+        write!(f, "}}")
+      }
+      Object::Range(start, end, false) => write!(f, "{}..{}", start, end),
+      Object::Range(start, end, true) => write!(f, "{}..={}", start, end),
+      Object::Function(_params, _body, _env) => write!(f, "[Function Object]"),
+      Object::Builtin(_) => write!(f, "[Builtin Function]"),
       Object::NoOp => write!(f, "NoOp"),
     }
   }
 }
 
+// Orders two numbers, None when either is NaN. Integers are compared to floats exactly rather than
+// after rounding them to a float.
+pub fn cmp_numbers(left: &Object, right: &Object) -> Option<Ordering> {
+  match (left, right) {
+    (Object::Integer(a), Object::Integer(b)) => return Some(a.cmp(b)),
+    (Object::Float(a), Object::Float(b)) => return a.partial_cmp(b),
+    (Object::Float(num), int) => {
+      return cmp_int_float(&int.to_bigint()?, *num).map(Ordering::reverse)
+    }
+    (int, Object::Float(num)) => return cmp_int_float(&int.to_bigint()?, *num),
+    _ => return Some(left.to_bigint()?.cmp(&right.to_bigint()?)),
+  }
+}
+
+fn cmp_int_float(int: &BigInt, num: f64) -> Option<Ordering> {
+  if num.is_nan() {
+    return None;
+  }
+  if num.is_infinite() {
+    return Some(if num > 0.0 {
+      Ordering::Less
+    } else {
+      Ordering::Greater
+    });
+  }
+
+  // Equal integer parts leave the fraction to decide
+  match int.cmp(&BigInt::from_f64(num.trunc())?) {
+    Ordering::Equal => return 0.0.partial_cmp(&num.fract()),
+    ordering => return Some(ordering),
+  }
+}
+
+// Quote nested strings so `["a, b"]` and `["a", "b"]` print differently
+fn write_nested(f: &mut fmt::Formatter<'_>, obj: &Object) -> fmt::Result {
+  match obj {
+    Object::String(val) => write!(f, "{:?}", val),
+    _ => write!(f, "{}", obj),
+  }
+}
+
+// The subset of objects that can be used as keys in a hash
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HashKey {
+  Integer(isize),
+  BigInt(BigInt),
+  Boolean(bool),
+  String(String),
+}
+
+impl From<HashKey> for Object {
+  fn from(key: HashKey) -> Self {
+    match key {
+      HashKey::Integer(num) => Object::Integer(num),
+      HashKey::BigInt(num) => Object::BigInt(num),
+      HashKey::Boolean(val) => Object::Boolean(val),
+      HashKey::String(val) => Object::String(val),
+    }
+  }
+}
+
+// Stores the result of big integer arithmetic as an Integer again whenever it fits
+impl From<BigInt> for Object {
+  fn from(num: BigInt) -> Self {
+    match num.to_isize() {
+      Some(num) => Object::Integer(num),
+      None => Object::BigInt(num),
+    }
+  }
+}
+
 pub enum Interrupt {
   Return(Object),
-  Error(String),
+  Error(RuntimeError),
+  // Leave the innermost loop with a value, or skip to its next iteration
+  Break(Object),
+  Continue,
+  // Stop the whole program with the given exit status
+  Exit(u8),
 }
 
 impl Interrupt {
   pub fn error(msg: String) -> EvalResult {
-    return Err(Interrupt::Error(msg));
+    return Err(Interrupt::Error(RuntimeError::new(msg)));
+  }
+
+  // Errors are created without a location, the innermost expression they pass through claims them
+  pub fn at(self, span: Span) -> Interrupt {
+    match self {
+      Interrupt::Error(RuntimeError { msg, span: None }) => Interrupt::Error(RuntimeError {
+        msg,
+        span: Some(span),
+      }),
+      interrupt => interrupt,
+    }
+  }
+}
+
+#[derive(Debug)]
+pub struct RuntimeError {
+  pub msg: String,
+  pub span: Option<Span>,
+}
+
+impl RuntimeError {
+  pub fn new(msg: String) -> Self {
+    RuntimeError { msg, span: None }
+  }
+}
+
+impl fmt::Display for RuntimeError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self.span {
+      Some(span) => write!(f, "{}: {}", span, self.msg),
+      None => write!(f, "{}", self.msg),
+    }
   }
 }
 
@@ -1,51 +1,108 @@
 use crate::{
-  ast::{self, BlockStatement, Expression, Precedence, Program, Statement}, lexer::Lexer, minions, token::Token
+  ast::{self, BlockStatement, Expression, Precedence, Program, Statement},
+  lexer::{Lexer, LexerError},
+  minions::MinionRoster,
+  token::{Span, Token},
 };
 use core::fmt;
+use std::rc::Rc;
 
 #[derive(Debug)]
 pub struct Parser<'a> {
   lexer: Lexer<'a>,
   curr_token: Token,
   peek_token: Token,
+  curr_span: Span,
+  peek_span: Span,
+  roster: Rc<MinionRoster>,
+  // Number of loop bodies being parsed, reset inside function bodies
+  loop_depth: usize,
   pub errors: Vec<ParserError>,
 }
 
 #[derive(Debug)]
 pub enum ParserError {
-  // expected, got
-  UnexpectedToken(Token, Token),
-  UnknownPrefix(Token),
-  InvalidIdent(Token)
+  // expected, got, location of got
+  UnexpectedToken(Token, Token, Span),
+  UnknownPrefix(Token, Span),
+  // identifier, suggested valid names, whether only exact roster names are accepted
+  InvalidIdent(Token, Vec<String>, bool, Span),
+  // assignment operator, location of the target
+  InvalidAssignment(Token, Span),
+  // `break` or `continue`
+  OutsideLoop(Token, Span),
+  // location of the ILLEGAL token
+  Lexer(LexerError, Span),
 }
 
-impl fmt::Display for ParserError {
-  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl ParserError {
+  pub fn span(&self) -> Span {
     match self {
-      ParserError::UnexpectedToken(expected, got) => {
-        write!(f, "Expected: {}, got: {}", expected, got)
+      ParserError::UnexpectedToken(_, _, span)
+      | ParserError::UnknownPrefix(_, span)
+      | ParserError::InvalidIdent(_, _, _, span)
+      | ParserError::InvalidAssignment(_, span)
+      | ParserError::OutsideLoop(_, span)
+      | ParserError::Lexer(_, span) => *span,
+    }
+  }
+
+  pub fn message(&self) -> String {
+    match self {
+      ParserError::UnexpectedToken(expected, got, _) => {
+        format!("Expected: {}, got: {}", expected, got)
+      }
+      ParserError::UnknownPrefix(got, _) => format!("Unknown prefix, got: {}", got),
+      ParserError::InvalidIdent(ident, _, _, _) => format!("Invalid minion name, got: {}", ident),
+      ParserError::InvalidAssignment(op, _) => {
+        format!(
+          "Invalid left-hand side of {}, expected an identifier",
+          op.symbol()
+        )
       }
-      ParserError::UnknownPrefix(got) => write!(f, "Unknown prefix, got: {}", got),
-      ParserError::InvalidIdent(ident) => write!(f, "Invalid minion name, got: {}", ident)
+      ParserError::OutsideLoop(Token::BREAK, _) => "`break` outside of a loop".to_string(),
+      ParserError::OutsideLoop(_, _) => "`continue` outside of a loop".to_string(),
+      ParserError::Lexer(error, _) => error.message(),
     }
   }
 }
 
+impl fmt::Display for ParserError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}: {}", self.span(), self.message())
+  }
+}
+
 impl<'a> Parser<'a> {
-  pub fn new(mut lexer: Lexer<'a>) -> Self {
-    let curr_token = lexer.next_token();
-    let peek_token = lexer.next_token();
-    Parser {
+  pub fn new(lexer: Lexer<'a>, roster: Rc<MinionRoster>) -> Self {
+    let mut parser = Parser {
       lexer,
-      curr_token,
-      peek_token,
+      curr_token: Token::EOF,
+      peek_token: Token::EOF,
+      curr_span: Span::default(),
+      peek_span: Span::default(),
+      roster,
+      loop_depth: 0,
       errors: vec![],
-    }
+    };
+
+    parser.next_token();
+    parser.next_token();
+    return parser;
   }
 
   fn next_token(&mut self) {
     self.curr_token = self.peek_token.clone();
-    self.peek_token = self.lexer.next_token();
+    self.curr_span = self.peek_span;
+    (self.peek_token, self.peek_span) = self.lexer.next_token();
+    if let Some(error) = self.lexer.take_error() {
+      self.errors.push(ParserError::Lexer(error, self.peek_span));
+    }
+  }
+
+  // Span from `start` up to and including the current token
+  fn span_from(&self, start: Span) -> Span {
+    return start.to(self.curr_span);
   }
 
   pub fn parse_program(&mut self) -> Program {
@@ -65,31 +122,41 @@ impl<'a> Parser<'a> {
     match self.curr_token {
       Token::LET => self.parse_let_statement(),
       Token::RETURN => self.parse_return_statement(),
+      Token::BREAK | Token::CONTINUE => self.parse_loop_control_statement(),
       _ => self.parse_expression_statement(),
     }
   }
 
   fn parse_expression_statement(&mut self) -> Option<Statement> {
-    let parse_expression = self.parse_expression(Precedence::LOWEST);
-    let statement = Statement::Expression(parse_expression?);
+    let expression = self.parse_expression(Precedence::LOWEST);
+    // Like `let` and `return`, skip the semicolon even after an error so it isn't reported too
     if self.peek_token == Token::SEMICOLON {
       self.next_token();
     }
 
-    return Some(statement);
+    return Some(Statement::Expression(expression?));
   }
 
   fn parse_expression(&mut self, precedence: Precedence) -> Option<Expression> {
-    let mut left = match &self.curr_token {
-      Token::IDENT(_) => Some(Expression::Ident(self.curr_token.clone())),
-      Token::INT(_) => Some(Expression::Integer(self.curr_token.clone())),
-      Token::BANG | Token::MINUS => self.parse_prefix_expression(),
-      Token::TRUE | Token::FALSE => Some(Expression::Boolean(self.curr_token.clone())),
+    let token = self.curr_token.clone();
+    let span = self.curr_span;
+    let mut left = match token {
+      Token::IDENT(_) => Some(Expression::Ident(token, span)),
+      Token::INT(_) | Token::BIGINT(_) => Some(Expression::Integer(token, span)),
+      Token::FLOAT(_) => Some(Expression::Float(token, span)),
+      Token::STRING(_) => Some(Expression::String(token, span)),
+      Token::BANG | Token::MINUS | Token::TILDE => self.parse_prefix_expression(),
+      Token::TRUE | Token::FALSE => Some(Expression::Boolean(token, span)),
       Token::LPAREN => self.parse_grouped_expression(),
       Token::IF => self.parse_if_expression(),
+      Token::WHILE => self.parse_while_expression(),
+      Token::FOR => self.parse_for_expression(),
       Token::FUNCTION => self.parse_function_expression(),
+      Token::LBRACKET => self.parse_array_expression(),
+      // Blocks only ever follow `if`, `else`, `fn` and loops, so a brace here is a hash literal
+      Token::LBRACE => self.parse_hash_expression(),
       _ => {
-        self.prefix_error(self.curr_token.clone());
+        self.prefix_error(token);
         return None;
       }
     };
@@ -104,14 +171,39 @@ impl<'a> Parser<'a> {
         | Token::EQ
         | Token::NOTEQ
         | Token::LT
-        | Token::GT => {
+        | Token::GT
+        | Token::LTEQ
+        | Token::GTEQ
+        | Token::PERCENT
+        | Token::POWER
+        | Token::AMPERSAND
+        | Token::PIPE
+        | Token::CARET
+        | Token::SHL
+        | Token::SHR
+        | Token::DOTDOT
+        | Token::DOTDOTEQ
+        | Token::AND
+        | Token::OR => {
           self.next_token();
           self.parse_infix_expression(left?)
         }
+        Token::ASSIGN
+        | Token::PLUSASSIGN
+        | Token::MINUSASSIGN
+        | Token::ASTERISKASSIGN
+        | Token::SLASHASSIGN => {
+          self.next_token();
+          self.parse_assign_expression(left?)
+        }
         Token::LPAREN if valid_function_identifier(&left) => {
           self.next_token();
           self.parse_call_expression(left?)
         }
+        Token::LBRACKET => {
+          self.next_token();
+          self.parse_index_expression(left?)
+        }
         _ => {
           return left;
         }
@@ -122,34 +214,83 @@ impl<'a> Parser<'a> {
   }
 
   fn parse_call_expression(&mut self, function: Expression) -> Option<Expression> {
-    let arguments = self.parse_call_args()?;
-    return Some(Expression::Call(Box::new(function), arguments));
+    let arguments = self.parse_expression_list(Token::RPAREN)?;
+    let span = self.span_from(function.span());
+    return Some(Expression::Call(Box::new(function), arguments, span));
   }
 
-  fn parse_call_args(&mut self) -> Option<Vec<Expression>> {
-    let mut args: Vec<Expression> = vec![];
-    if self.peek_token == Token::RPAREN {
+  fn parse_array_expression(&mut self) -> Option<Expression> {
+    let start = self.curr_span;
+    let elements = self.parse_expression_list(Token::RBRACKET)?;
+    return Some(Expression::Array(elements, self.span_from(start)));
+  }
+
+  fn parse_hash_expression(&mut self) -> Option<Expression> {
+    let start = self.curr_span;
+    let mut pairs: Vec<(Expression, Expression)> = vec![];
+
+    while self.peek_token != Token::RBRACE {
+      self.next_token();
+      let key = self.parse_expression(Precedence::LOWEST)?;
+
+      if !self.expect_peek_token(Token::COLON) {
+        return None;
+      }
+
+      self.next_token();
+      let value = self.parse_expression(Precedence::LOWEST)?;
+      pairs.push((key, value));
+
+      if self.peek_token != Token::RBRACE && !self.expect_peek_token(Token::COMMA) {
+        return None;
+      }
+    }
+
+    if !self.expect_peek_token(Token::RBRACE) {
+      return None;
+    }
+
+    return Some(Expression::Hash(pairs, self.span_from(start)));
+  }
+
+  fn parse_index_expression(&mut self, left: Expression) -> Option<Expression> {
+    self.next_token();
+    let index = self.parse_expression(Precedence::LOWEST)?;
+
+    if !self.expect_peek_token(Token::RBRACKET) {
+      return None;
+    }
+
+    let span = self.span_from(left.span());
+    return Some(Expression::Index(Box::new(left), Box::new(index), span));
+  }
+
+  // Parses comma separated expressions up to and including the `end` token
+  fn parse_expression_list(&mut self, end: Token) -> Option<Vec<Expression>> {
+    let mut list: Vec<Expression> = vec![];
+    if self.peek_token == end {
       self.next_token();
-      return Some(args);
+      return Some(list);
     }
 
     self.next_token();
-    args.push(self.parse_expression(Precedence::LOWEST)?);
+    list.push(self.parse_expression(Precedence::LOWEST)?);
 
     while self.peek_token == Token::COMMA {
       self.next_token();
       self.next_token();
-      args.push(self.parse_expression(Precedence::LOWEST)?);
+      list.push(self.parse_expression(Precedence::LOWEST)?);
     }
 
-    if !self.expect_peek_token(Token::RPAREN) {
+    if !self.expect_peek_token(end) {
       return None;
     }
 
-    return Some(args);
+    return Some(list);
   }
 
   fn parse_function_expression(&mut self) -> Option<Expression> {
+    let start = self.curr_span;
     if !self.expect_peek_token(Token::LPAREN) {
       return None;
     }
@@ -160,9 +301,12 @@ impl<'a> Parser<'a> {
       return None;
     }
 
-    let body = self.parse_block_statement()?;
+    // A function body is not part of the loop it is defined in
+    let loop_depth = std::mem::take(&mut self.loop_depth);
+    let body = self.parse_block_statement();
+    self.loop_depth = loop_depth;
 
-    return Some(Expression::Function(params, body));
+    return Some(Expression::Function(params, body?, self.span_from(start)));
   }
 
   fn parse_function_params(&mut self) -> Option<Vec<Expression>> {
@@ -174,19 +318,12 @@ impl<'a> Parser<'a> {
     }
 
     self.next_token();
-    
-    if !minions::is_valid_minion(&self.curr_token) {
-      self.errors.push(ParserError::InvalidIdent(self.curr_token.clone()));
-    }
+    params.push(self.parse_function_param());
 
-    params.push(Expression::Ident(self.curr_token.clone()));
     while self.peek_token == Token::COMMA {
       self.next_token();
       self.next_token();
-      if !minions::is_valid_minion(&self.curr_token) {
-        self.errors.push(ParserError::InvalidIdent(self.curr_token.clone()));
-      }
-      params.push(Expression::Ident(self.curr_token.clone()));
+      params.push(self.parse_function_param());
     }
 
     if !self.expect_peek_token(Token::RPAREN) {
@@ -196,7 +333,16 @@ impl<'a> Parser<'a> {
     return Some(params);
   }
 
+  fn parse_function_param(&mut self) -> Expression {
+    if !self.roster.is_valid(&self.curr_token) {
+      self.invalid_ident_error(self.curr_token.clone(), self.curr_span);
+    }
+
+    return Expression::Ident(self.curr_token.clone(), self.curr_span);
+  }
+
   fn parse_if_expression(&mut self) -> Option<Expression> {
+    let start = self.curr_span;
     if !self.expect_peek_token(Token::LPAREN) {
       return None;
     }
@@ -227,6 +373,7 @@ impl<'a> Parser<'a> {
       Box::new(condition),
       consequence,
       alternative,
+      self.span_from(start),
     ));
   }
 
@@ -244,6 +391,7 @@ impl<'a> Parser<'a> {
       self.errors.push(ParserError::UnexpectedToken(
         Token::RBRACE,
         self.curr_token.clone(),
+        self.curr_span,
       ));
 
       return None;
@@ -265,41 +413,171 @@ impl<'a> Parser<'a> {
 
   fn parse_infix_expression(&mut self, left: Expression) -> Option<Expression> {
     let curr = self.curr_token.clone();
-    let curr_precedence = ast::get_precedence(&curr);
+    let curr_precedence = match curr {
+      // Right associative, parse `2 ** 3 ** 2` as `2 ** (3 ** 2)`
+      Token::POWER => Precedence::PREFIX,
+      _ => ast::get_precedence(&curr),
+    };
     self.next_token();
+    let right = self.parse_expression(curr_precedence)?;
+    let span = self.span_from(left.span());
     return Some(Expression::Infix(
       curr,
       Box::new(left),
-      Box::new(self.parse_expression(curr_precedence)?),
+      Box::new(right),
+      span,
     ));
   }
 
+  // Assignment is right associative, so `kevin = bob = 1` assigns to both
+  fn parse_assign_expression(&mut self, target: Expression) -> Option<Expression> {
+    let op = self.curr_token.clone();
+    let ident = match target {
+      Expression::Ident(ref ident, _) => ident.clone(),
+      _ => {
+        self
+          .errors
+          .push(ParserError::InvalidAssignment(op, target.span()));
+        return None;
+      }
+    };
+
+    self.next_token();
+    let value = self.parse_expression(Precedence::LOWEST)?;
+    let span = self.span_from(target.span());
+    return Some(Expression::Assign(op, ident, Box::new(value), span));
+  }
+
   fn parse_prefix_expression(&mut self) -> Option<Expression> {
     let curr = self.curr_token.clone();
+    let start = self.curr_span;
     self.next_token();
+    let right = self.parse_expression(Precedence::PREFIX)?;
     return Some(Expression::Prefix(
       curr,
-      Box::new(self.parse_expression(Precedence::PREFIX)?),
+      Box::new(right),
+      self.span_from(start),
     ));
   }
 
   fn parse_return_statement(&mut self) -> Option<Statement> {
+    let start = self.curr_span;
     self.next_token();
     let expression = self.parse_expression(Precedence::LOWEST);
     if self.peek_token == Token::SEMICOLON {
       self.next_token();
     }
-    return Some(Statement::Return(expression?));
+    return Some(Statement::Return(expression?, self.span_from(start)));
+  }
+
+  fn parse_while_expression(&mut self) -> Option<Expression> {
+    let start = self.curr_span;
+    if !self.expect_peek_token(Token::LPAREN) {
+      return None;
+    }
+
+    self.next_token();
+    let condition = self.parse_expression(Precedence::LOWEST)?;
+
+    if !self.expect_peek_token(Token::RPAREN) {
+      return None;
+    }
+    if !self.expect_peek_token(Token::LBRACE) {
+      return None;
+    }
+
+    let body = self.parse_loop_body()?;
+    return Some(Expression::While(
+      Box::new(condition),
+      body,
+      self.span_from(start),
+    ));
+  }
+
+  fn parse_for_expression(&mut self) -> Option<Expression> {
+    let start = self.curr_span;
+    if !self.expect_peek_token(Token::LPAREN) {
+      return None;
+    }
+    if !self.expect_peek_token(Token::IDENT(String::new())) {
+      return None;
+    }
+
+    let ident = self.curr_token.clone();
+    if !self.roster.is_valid(&ident) {
+      self.invalid_ident_error(ident.clone(), self.curr_span);
+    }
+
+    if !self.expect_peek_token(Token::IN) {
+      return None;
+    }
+
+    self.next_token();
+    let collection = self.parse_expression(Precedence::LOWEST)?;
+
+    if !self.expect_peek_token(Token::RPAREN) {
+      return None;
+    }
+    if !self.expect_peek_token(Token::LBRACE) {
+      return None;
+    }
+
+    let body = self.parse_loop_body()?;
+    return Some(Expression::For(
+      ident,
+      Box::new(collection),
+      body,
+      self.span_from(start),
+    ));
+  }
+
+  fn parse_loop_body(&mut self) -> Option<BlockStatement> {
+    self.loop_depth += 1;
+    let body = self.parse_block_statement();
+    self.loop_depth -= 1;
+    return body;
+  }
+
+  fn parse_loop_control_statement(&mut self) -> Option<Statement> {
+    let token = self.curr_token.clone();
+    let start = self.curr_span;
+    if self.loop_depth == 0 {
+      self
+        .errors
+        .push(ParserError::OutsideLoop(token.clone(), start));
+    }
+
+    let statement = match token {
+      Token::BREAK
+        if !matches!(
+          self.peek_token,
+          Token::SEMICOLON | Token::RBRACE | Token::EOF
+        ) =>
+      {
+        self.next_token();
+        let value = self.parse_expression(Precedence::LOWEST)?;
+        Statement::Break(Some(value), self.span_from(start))
+      }
+      Token::BREAK => Statement::Break(None, start),
+      _ => Statement::Continue(start),
+    };
+
+    if self.peek_token == Token::SEMICOLON {
+      self.next_token();
+    }
+
+    return Some(statement);
   }
 
   fn parse_let_statement(&mut self) -> Option<Statement> {
+    let start = self.curr_span;
     if !self.expect_peek_token(Token::IDENT(String::new())) {
       return None;
     }
 
     let ident = self.curr_token.clone();
-    if !minions::is_valid_minion(&ident) {
-      self.errors.push(ParserError::InvalidIdent(ident.clone()));
+    if !self.roster.is_valid(&ident) {
+      self.invalid_ident_error(ident.clone(), self.curr_span);
     }
 
     if !self.expect_peek_token(Token::ASSIGN) {
@@ -313,7 +591,7 @@ impl<'a> Parser<'a> {
       self.next_token();
     }
 
-    return Some(Statement::Let(ident, expression?));
+    return Some(Statement::Let(ident, expression?, self.span_from(start)));
   }
 
   fn expect_peek_token(&mut self, token: Token) -> bool {
@@ -327,20 +605,50 @@ impl<'a> Parser<'a> {
   }
 
   fn peek_error(&mut self, token: Token) {
-    self
-      .errors
-      .push(ParserError::UnexpectedToken(token, self.peek_token.clone()))
+    self.errors.push(ParserError::UnexpectedToken(
+      token,
+      self.peek_token.clone(),
+      self.peek_span,
+    ))
+  }
+
+  fn invalid_ident_error(&mut self, ident: Token, span: Span) {
+    let suggestions = match &ident {
+      Token::IDENT(name) => self.roster.suggestions(name),
+      _ => vec![],
+    };
+    self.errors.push(ParserError::InvalidIdent(
+      ident,
+      suggestions,
+      self.roster.is_exact(),
+      span,
+    ));
   }
 
   fn prefix_error(&mut self, token: Token) {
-    self.errors.push(ParserError::UnknownPrefix(token))
+    // Don't pile onto an error the lexer already reported for this token
+    let span = self.curr_span;
+    if token == Token::ILLEGAL
+      && self
+        .errors
+        .iter()
+        .any(|error| matches!(error, ParserError::Lexer(_, at) if *at == span))
+    {
+      return;
+    }
+
+    self
+      .errors
+      .push(ParserError::UnknownPrefix(token, self.curr_span))
   }
 }
 
 fn valid_function_identifier(left: &Option<Expression>) -> bool {
-  match left {
-    Some(Expression::Ident(_)) => true,
-    Some(Expression::Function(_, _)) => true,
-    _ => false,
-  }
+  matches!(
+    left,
+    Some(Expression::Ident(..))
+      | Some(Expression::Function(..))
+      | Some(Expression::Call(..))
+      | Some(Expression::Index(..))
+  )
 }
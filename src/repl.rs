@@ -0,0 +1,282 @@
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::process::ExitCode;
+
+use crate::diagnostics::Diagnostic;
+use crate::editor::{Editor, ReadLine};
+use crate::eval::Evaluator;
+use crate::lexer::Lexer;
+use crate::object::{Interrupt, Object};
+use crate::parser::{Parser, ParserError};
+use crate::sigint;
+use crate::token::Token;
+use crate::Config;
+
+const HELP: &str = ":env            List the bindings in the current session
+:ast <code>     Show the parsed program for <code>
+:tokens <code>  Show the tokens the lexer produces for <code>
+:load <file>    Evaluate a file into the current session
+:reset          Clear every binding in the session
+:help           Show this message
+:quit           Exit the REPL";
+
+struct Repl<'a> {
+  config: &'a Config,
+  eval: Evaluator,
+  editor: Option<Editor>,
+  // Everything evaluated so far, so errors in functions from earlier inputs can still be shown
+  session: String,
+}
+
+pub fn start(config: &Config) -> ExitCode {
+  let mut repl = Repl {
+    config,
+    eval: Evaluator::new(config.max_call_depth),
+    editor: Editor::is_supported().then(Editor::new),
+    session: String::new(),
+  };
+  sigint::install();
+
+  println!("Welcome to the Minion REPL 🍌🍌🍌🍌");
+  println!("-----------------------------------");
+
+  let mut input = String::new();
+  loop {
+    // Keep reading lines until the input stops looking unfinished
+    let prompt = if input.is_empty() { ">>> " } else { "... " };
+    let completions = repl.completions();
+    let read = match &mut repl.editor {
+      Some(editor) => editor.read_line(prompt, &completions),
+      None => {
+        print!("{}", prompt);
+        io::stdout().flush().unwrap();
+        read_line()
+      }
+    };
+
+    match read {
+      Ok(ReadLine::Line(line)) => input.push_str(&line),
+      Ok(ReadLine::Eof) => {
+        println!();
+        return ExitCode::SUCCESS;
+      }
+      // Ctrl-C throws away everything typed so far, including earlier lines of unfinished input
+      Ok(ReadLine::Cancelled) => {
+        println!();
+        input.clear();
+        continue;
+      }
+      Err(err) => {
+        eprintln!("Failed to read repl line: {}", err);
+        return ExitCode::FAILURE;
+      }
+    }
+
+    if !input.trim_start().starts_with(':') && is_incomplete(&input, config) {
+      continue;
+    }
+
+    // Multi-line input is recalled as a single line, newlines are only whitespace to the lexer
+    if let Some(editor) = &mut repl.editor {
+      let lines: Vec<&str> = input.lines().map(str::trim).collect();
+      editor.add_history(lines.join(" ").trim());
+    }
+
+    let exit = if input.trim_start().starts_with(':') {
+      repl.run_command(input.trim())
+    } else {
+      repl.eval_input(&input, true)
+    };
+
+    if let Some(code) = exit {
+      return code;
+    }
+    input.clear();
+  }
+}
+
+impl Repl<'_> {
+  // Evaluates `code` as the next part of the session, returning the exit code if it called `exit`
+  fn eval_input(&mut self, code: &str, print_result: bool) -> Option<ExitCode> {
+    let start = self.session.len();
+    self.session.push_str(code);
+    if !self.session.ends_with('\n') {
+      self.session.push('\n');
+    }
+
+    let mut parser = Parser::new(
+      Lexer::starting_at(&self.session, start),
+      self.config.roster.clone(),
+    );
+    let program = parser.parse_program();
+    let errors = parser.errors;
+
+    if !errors.is_empty() {
+      for error in &errors {
+        self.report(Diagnostic::from(error));
+      }
+      return None;
+    }
+
+    // Forget a Ctrl-C that arrived while typing so it doesn't stop this evaluation
+    sigint::take();
+    match self.eval.eval(program) {
+      Ok(Object::NoOp) => (),
+      Ok(obj) | Err(Interrupt::Return(obj)) => {
+        if print_result {
+          println!("{}", obj);
+        }
+      }
+      Err(Interrupt::Exit(code)) => return Some(ExitCode::from(code)),
+      // The parser rejects loop control outside of a loop
+      Err(Interrupt::Break(_) | Interrupt::Continue) => unreachable!(),
+      Err(Interrupt::Error(err)) => self.report(Diagnostic::from(&err)),
+    }
+
+    return None;
+  }
+
+  // Names worth tab completing: everything bound in the session and the roster of minions
+  fn completions(&self) -> Vec<String> {
+    let mut words: Vec<String> = self.eval.env.borrow().store.keys().cloned().collect();
+    words.extend(self.config.roster.names().iter().cloned());
+    return words;
+  }
+
+  fn report(&self, diagnostic: Diagnostic) {
+    print!(
+      "{}",
+      diagnostic.render("<repl>", &self.session, self.config.color)
+    );
+  }
+
+  fn run_command(&mut self, line: &str) -> Option<ExitCode> {
+    let (command, arg) = match line.split_once(char::is_whitespace) {
+      Some((command, arg)) => (command, arg.trim()),
+      None => (line, ""),
+    };
+
+    match command {
+      ":env" => self.print_env(),
+      ":ast" => self.print_ast(arg),
+      ":tokens" => print_tokens(arg),
+      ":load" => match fs::read_to_string(arg) {
+        Ok(source) => return self.eval_input(&source, false),
+        Err(err) => println!("Cannot read {}: {}", arg, err),
+      },
+      ":reset" => {
+        self.eval = Evaluator::new(self.config.max_call_depth);
+        println!("Environment cleared");
+      }
+      ":help" => println!("{}", HELP),
+      ":quit" => return Some(ExitCode::SUCCESS),
+      _ => println!("Unknown command {}, try :help", command),
+    }
+
+    return None;
+  }
+
+  fn print_env(&self) {
+    let env = self.eval.env.borrow();
+    let mut names: Vec<&String> = env.store.keys().collect();
+    names.sort();
+
+    for name in names {
+      let value = &env.store[name];
+      println!("{}: {} = {}", name, value.type_name(), value);
+    }
+  }
+
+  fn print_ast(&self, code: &str) {
+    let mut parser = Parser::new(Lexer::new(code), self.config.roster.clone());
+    let program = parser.parse_program();
+
+    for error in &parser.errors {
+      print!(
+        "{}",
+        Diagnostic::from(error).render("<ast>", code, self.config.color)
+      );
+    }
+    for statement in program {
+      println!("{:#?}", statement);
+    }
+  }
+}
+
+fn print_tokens(code: &str) {
+  let mut lexer = Lexer::new(code);
+  loop {
+    let (token, span) = lexer.next_token();
+    println!("{:<8} {:?}", span.to_string(), token);
+    if token == Token::EOF {
+      break;
+    }
+  }
+}
+
+// Reads a line of stdin when line editing isn't available. Reads directly from the buffer instead
+// of using `Stdin::read_line`, which retries when Ctrl-C interrupts the read.
+fn read_line() -> io::Result<ReadLine> {
+  let mut stdin = io::stdin().lock();
+  let mut line: Vec<u8> = vec![];
+
+  loop {
+    let available = match stdin.fill_buf() {
+      Ok(available) => available,
+      Err(err) if err.kind() == io::ErrorKind::Interrupted => {
+        if sigint::take() {
+          return Ok(ReadLine::Cancelled);
+        }
+        continue;
+      }
+      Err(err) => return Err(err),
+    };
+
+    if available.is_empty() {
+      if line.is_empty() {
+        return Ok(ReadLine::Eof);
+      }
+      break;
+    }
+
+    match available.iter().position(|&byte| byte == b'\n') {
+      Some(idx) => {
+        line.extend_from_slice(&available[..=idx]);
+        stdin.consume(idx + 1);
+        break;
+      }
+      None => {
+        let len = available.len();
+        line.extend_from_slice(available);
+        stdin.consume(len);
+      }
+    }
+  }
+
+  return Ok(ReadLine::Line(String::from_utf8_lossy(&line).into_owned()));
+}
+
+// Input is unfinished when it has unclosed brackets or the parser ran out of tokens
+fn is_incomplete(input: &str, config: &Config) -> bool {
+  let mut lexer = Lexer::new(input);
+  let mut depth = 0;
+  loop {
+    match lexer.next_token().0 {
+      Token::LPAREN | Token::LBRACE | Token::LBRACKET => depth += 1,
+      Token::RPAREN | Token::RBRACE | Token::RBRACKET => depth -= 1,
+      Token::EOF => break,
+      _ => (),
+    }
+  }
+
+  let mut parser = Parser::new(Lexer::new(input), config.roster.clone());
+  parser.parse_program();
+
+  return depth > 0
+    || parser.errors.iter().any(|error| {
+      matches!(
+        error,
+        ParserError::UnexpectedToken(_, Token::EOF, _) | ParserError::UnknownPrefix(Token::EOF, _)
+      )
+    });
+}
@@ -0,0 +1,79 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Set by the Ctrl-C handler, the REPL and evaluator poll it instead of the process being killed
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(unix)]
+mod ffi {
+  use std::ffi::c_int;
+
+  pub const SIGINT: c_int = 2;
+
+  #[cfg(target_os = "linux")]
+  pub const SIG_BLOCK: c_int = 0;
+  #[cfg(target_os = "linux")]
+  pub const SIG_UNBLOCK: c_int = 1;
+  #[cfg(not(target_os = "linux"))]
+  pub const SIG_BLOCK: c_int = 1;
+  #[cfg(not(target_os = "linux"))]
+  pub const SIG_UNBLOCK: c_int = 2;
+
+  // Large enough for `sigset_t` on every unix we build for
+  pub type SigSet = [u64; 16];
+
+  extern "C" {
+    pub fn signal(signum: c_int, handler: extern "C" fn(c_int)) -> usize;
+    pub fn siginterrupt(signum: c_int, flag: c_int) -> c_int;
+    pub fn sigemptyset(set: *mut SigSet) -> c_int;
+    pub fn sigaddset(set: *mut SigSet, signum: c_int) -> c_int;
+    pub fn pthread_sigmask(how: c_int, set: *const SigSet, old: *mut SigSet) -> c_int;
+  }
+}
+
+#[cfg(unix)]
+extern "C" fn handle_sigint(_signum: std::ffi::c_int) {
+  INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+// Catch Ctrl-C instead of exiting. Blocking reads are interrupted rather than restarted so a
+// pending `read` returns and the caller gets to check `take`.
+pub fn install() {
+  #[cfg(unix)]
+  unsafe {
+    ffi::signal(ffi::SIGINT, handle_sigint);
+    ffi::siginterrupt(ffi::SIGINT, 1);
+  }
+}
+
+// Stop Ctrl-C from being delivered to the calling thread. Threads it spawns inherit this, so they
+// have to `unblock` it again.
+pub fn block() {
+  set_blocked(true);
+}
+
+// Let the calling thread receive Ctrl-C again after `block`
+pub fn unblock() {
+  set_blocked(false);
+}
+
+fn set_blocked(blocked: bool) {
+  #[cfg(unix)]
+  unsafe {
+    let mut set: ffi::SigSet = [0; 16];
+    ffi::sigemptyset(&mut set);
+    ffi::sigaddset(&mut set, ffi::SIGINT);
+    let how = if blocked {
+      ffi::SIG_BLOCK
+    } else {
+      ffi::SIG_UNBLOCK
+    };
+    ffi::pthread_sigmask(how, &set, std::ptr::null_mut());
+  }
+  #[cfg(not(unix))]
+  let _ = blocked;
+}
+
+// Whether Ctrl-C was pressed since the last call
+pub fn take() -> bool {
+  return INTERRUPTED.swap(false, Ordering::SeqCst);
+}
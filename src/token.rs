@@ -1,5 +1,7 @@
 use core::fmt;
 
+use crate::bigint::BigInt;
+
 #[derive(Debug, PartialEq, Clone, PartialOrd)]
 pub enum Token {
   ILLEGAL,
@@ -7,26 +9,51 @@ pub enum Token {
 
   IDENT(String),
   INT(isize),
+  // Integer literals too large for an INT
+  BIGINT(BigInt),
+  FLOAT(f64),
+  STRING(String),
 
   ASSIGN,
+  PLUSASSIGN,
+  MINUSASSIGN,
+  ASTERISKASSIGN,
+  SLASHASSIGN,
   PLUS,
   MINUS,
   BANG,
   ASTERISK,
   SLASH,
+  PERCENT,
+  POWER,
+  TILDE,
+  AMPERSAND,
+  PIPE,
+  CARET,
+  SHL,
+  SHR,
 
   LT,
   GT,
+  LTEQ,
+  GTEQ,
+  DOTDOT,
+  DOTDOTEQ,
   EQ,
   NOTEQ,
+  AND,
+  OR,
 
   COMMA,
   SEMICOLON,
+  COLON,
 
   LPAREN,
   RPAREN,
   LBRACE,
   RBRACE,
+  LBRACKET,
+  RBRACKET,
 
   FUNCTION,
   LET,
@@ -35,14 +62,92 @@ pub enum Token {
   IF,
   ELSE,
   RETURN,
+  WHILE,
+  FOR,
+  IN,
+  BREAK,
+  CONTINUE,
 }
 
 impl fmt::Display for Token {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     match self {
       Token::IDENT(ident) => write!(f, "{}", ident),
-      Token::INT(_) => write!(f, "INTEGER"),
+      Token::INT(_) | Token::BIGINT(_) => write!(f, "INTEGER"),
+      Token::FLOAT(_) => write!(f, "FLOAT"),
+      Token::STRING(_) => write!(f, "STRING"),
       _ => write!(f, "{:?}", self),
     }
   }
 }
+
+impl Token {
+  // How an operator is written in source, for messages about it. Other tokens use their `Display`.
+  pub fn symbol(&self) -> String {
+    let symbol = match self {
+      Token::ASSIGN => "=",
+      Token::PLUSASSIGN => "+=",
+      Token::MINUSASSIGN => "-=",
+      Token::ASTERISKASSIGN => "*=",
+      Token::SLASHASSIGN => "/=",
+      Token::PLUS => "+",
+      Token::MINUS => "-",
+      Token::BANG => "!",
+      Token::ASTERISK => "*",
+      Token::SLASH => "/",
+      Token::PERCENT => "%",
+      Token::POWER => "**",
+      Token::TILDE => "~",
+      Token::AMPERSAND => "&",
+      Token::PIPE => "|",
+      Token::CARET => "^",
+      Token::SHL => "<<",
+      Token::SHR => ">>",
+      Token::LT => "<",
+      Token::GT => ">",
+      Token::LTEQ => "<=",
+      Token::GTEQ => ">=",
+      Token::DOTDOT => "..",
+      Token::DOTDOTEQ => "..=",
+      Token::EQ => "==",
+      Token::NOTEQ => "!=",
+      Token::AND => "&&",
+      Token::OR => "||",
+      _ => return self.to_string(),
+    };
+    return symbol.to_string();
+  }
+}
+
+// Location of a token or AST node in the source, `start` and `end` are byte offsets while `line` and
+// `col` are the 1-based position of the first character
+#[derive(Clone, Copy, Default, PartialEq, PartialOrd)]
+pub struct Span {
+  pub start: usize,
+  pub end: usize,
+  pub line: usize,
+  pub col: usize,
+}
+
+impl Span {
+  // Span covering everything from the start of `self` to the end of `other`
+  pub fn to(&self, other: Span) -> Span {
+    Span {
+      end: other.end.max(self.end),
+      ..*self
+    }
+  }
+}
+
+impl fmt::Display for Span {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}:{}", self.line, self.col)
+  }
+}
+
+// Kept on one line so dumped ASTs stay readable
+impl fmt::Debug for Span {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "Span({}, {}..{})", self, self.start, self.end)
+  }
+}